@@ -0,0 +1,90 @@
+// Packed 8 bit pixels against the four f32 layout they replaced, at the example's buffer size.
+// Run with `cargo bench -p engine`.
+use std::hint::black_box;
+use std::mem::size_of_val;
+use std::time::Instant;
+use engine::blend::GsnBlendMode;
+use engine::renderer::{pixel_rgba, GsnSprite, Pixel, BLACK};
+
+const WIDTH: u32 = 1024;
+const HEIGHT: u32 = 786;
+const ITERATIONS: u32 = 100;
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct F32Pixel {
+    r: f32,
+    g: f32,
+    b: f32,
+    a: f32
+}
+
+fn bench<F: FnMut()>(name: &str, bytes: usize, mut f: F) {
+    f();
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+    let per_frame = start.elapsed() / ITERATIONS;
+    println!("{:<24} {:>9.3} ms {:>9.2} MB", name, per_frame.as_secs_f64() * 1000.0, bytes as f64 / 1e6);
+}
+
+fn main() {
+    let len = WIDTH as usize * HEIGHT as usize;
+    let mut floats = vec![F32Pixel { r: 0.0, g: 0.0, b: 0.0, a: 1.0 }; len];
+    let mut sprite = GsnSprite::new(WIDTH, HEIGHT);
+    let float_bytes = size_of_val(floats.as_slice());
+    let packed_bytes = sprite.as_bytes().len();
+
+    println!("{:<24} {:>12} {:>12}", "", "per frame", "buffer");
+    bench("clear f32", float_bytes, || {
+        floats.fill(F32Pixel { r: 0.0, g: 0.0, b: 0.0, a: 1.0 });
+        black_box(&floats);
+    });
+    bench("clear u8", packed_bytes, || {
+        sprite.clear(BLACK);
+        black_box(&sprite);
+    });
+
+    // What an upload has to read, copied into a staging buffer as the driver would.
+    let mut staging = vec![0u8; float_bytes];
+    bench("upload copy f32", float_bytes, || {
+        let src = unsafe { std::slice::from_raw_parts(floats.as_ptr().cast::<u8>(), float_bytes) };
+        staging.copy_from_slice(src);
+        black_box(&staging);
+    });
+    bench("upload copy u8", packed_bytes, || {
+        staging[..packed_bytes].copy_from_slice(sprite.as_bytes());
+        black_box(&staging);
+    });
+
+    // The same inline source over loop on both layouts, the u8 one pays for converting to floats and back.
+    let (sr, sg, sb, sa) = (1.0, 0.5, 0.25, 0.5);
+    let over = |[dr, dg, db, da]: [f32; 4]| -> [f32; 4] {
+        let a = sa + da * (1.0 - sa);
+        let blend = |s: f32, d: f32| (s * sa + d * da * (1.0 - sa)) / a;
+        [blend(sr, dr), blend(sg, dg), blend(sb, db), a]
+    };
+    bench("alpha loop f32", float_bytes, || {
+        for p in floats.iter_mut() {
+            let [r, g, b, a] = over([p.r, p.g, p.b, p.a]);
+            *p = F32Pixel { r, g, b, a };
+        }
+        black_box(&floats);
+    });
+    let mut packed = vec![BLACK; len];
+    bench("alpha loop u8", packed_bytes, || {
+        for p in packed.iter_mut() {
+            let [r, g, b, a] = over(p.to_f32());
+            *p = Pixel::from_f32(r, g, b, a);
+        }
+        black_box(&packed);
+    });
+
+    // What drawing actually costs: fill_rect goes through set_pixel, the blend mode and dirty tracking.
+    sprite.set_blend_mode(GsnBlendMode::Alpha);
+    bench("alpha fill_rect u8", packed_bytes, || {
+        sprite.fill_rect(0, 0, WIDTH, HEIGHT, pixel_rgba(255, 128, 64, 128));
+        black_box(&sprite);
+    });
+}
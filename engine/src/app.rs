@@ -0,0 +1,14 @@
+use crate::{GsnEngine, GsnEvent};
+
+// Callbacks for GsnEngine::run. Only on_update is required, quit by calling engine.exit().
+pub trait GsnApp {
+    fn on_create(&mut self, _engine: &mut GsnEngine) {}
+
+    // Once a frame after the events, draw into engine.buffer() here. dt is in seconds.
+    fn on_update(&mut self, engine: &mut GsnEngine, dt: f64);
+
+    fn on_event(&mut self, _engine: &mut GsnEngine, _event: &GsnEvent) {}
+
+    // Runs once, after the loop ends.
+    fn on_destroy(&mut self, _engine: &mut GsnEngine) {}
+}
@@ -0,0 +1,76 @@
+use std::fmt;
+use std::sync::Arc;
+use crate::sprite::Pixel;
+
+// How a pixel being drawn (src) is combined with what is already in the sprite (dst).
+#[derive(Clone, Default)]
+pub enum GsnBlendMode {
+    // Overwrite dst.
+    #[default]
+    Normal,
+    // Overwrite dst, but only with fully opaque pixels.
+    Mask,
+    // src over dst.
+    Alpha,
+    // dst + src, weighted by src alpha.
+    Additive,
+    // dst * src, weighted by src alpha.
+    Multiply,
+    // 1 - (1 - dst) * (1 - src), weighted by src alpha.
+    Screen,
+    // Called as f(src, dst), the result is written as is.
+    Custom(Arc<dyn Fn(Pixel, Pixel) -> Pixel + Send + Sync>)
+}
+
+impl fmt::Debug for GsnBlendMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GsnBlendMode::Normal => write!(f, "Normal"),
+            GsnBlendMode::Mask => write!(f, "Mask"),
+            GsnBlendMode::Alpha => write!(f, "Alpha"),
+            GsnBlendMode::Additive => write!(f, "Additive"),
+            GsnBlendMode::Multiply => write!(f, "Multiply"),
+            GsnBlendMode::Screen => write!(f, "Screen"),
+            GsnBlendMode::Custom(_) => write!(f, "Custom")
+        }
+    }
+}
+
+impl GsnBlendMode {
+    pub fn custom<F: Fn(Pixel, Pixel) -> Pixel + Send + Sync + 'static>(f: F) -> GsnBlendMode {
+        GsnBlendMode::Custom(Arc::new(f))
+    }
+
+    // None means dst is left untouched. Blending is done in floats and rounded back to 8 bits.
+    pub(crate) fn apply(&self, src: Pixel, dst: Pixel) -> Option<Pixel> {
+        let [sr, sg, sb, sa] = src.to_f32();
+        let [dr, dg, db, da] = dst.to_f32();
+        let mix = |f: fn(f32, f32) -> f32| Pixel::from_f32(
+            lerp(dr, f(sr, dr), sa),
+            lerp(dg, f(sg, dg), sa),
+            lerp(db, f(sb, db), sa),
+            da
+        );
+        match self {
+            GsnBlendMode::Normal => Some(src),
+            GsnBlendMode::Mask => if src.a == u8::MAX { Some(src) } else { None },
+            GsnBlendMode::Alpha => {
+                let a = sa + da * (1.0 - sa);
+                if a <= 0.0 {
+                    return Some(Pixel { r: 0, g: 0, b: 0, a: 0 });
+                }
+                let inv = 1.0 / a;
+                let over = |s: f32, d: f32| (s * sa + d * da * (1.0 - sa)) * inv;
+                Some(Pixel::from_f32(over(sr, dr), over(sg, dg), over(sb, db), a))
+            }
+            GsnBlendMode::Additive => Some(Pixel::from_f32(dr + sr * sa, dg + sg * sa, db + sb * sa, da)),
+            GsnBlendMode::Multiply => Some(mix(|s, d| s * d)),
+            GsnBlendMode::Screen => Some(mix(|s, d| 1.0 - (1.0 - s) * (1.0 - d))),
+            GsnBlendMode::Custom(f) => Some(f(src, dst))
+        }
+    }
+}
+
+fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
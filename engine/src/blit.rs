@@ -0,0 +1,98 @@
+use crate::sprite::{GsnSprite, Pixel};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GsnRect {
+    pub x: i32,
+    pub y: i32,
+    pub w: u32,
+    pub h: u32
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum GsnColorKey {
+    #[default]
+    None,
+    // Source pixels equal to this color are not drawn.
+    Color(Pixel),
+    // Source pixels with an alpha below this (0-255) are not drawn.
+    Alpha(u8)
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct GsnBlit {
+    pub flip_x: bool,
+    pub flip_y: bool,
+    pub key: GsnColorKey
+}
+
+impl GsnRect {
+    pub fn new(x: i32, y: i32, w: u32, h: u32) -> GsnRect {
+        GsnRect { x, y, w, h }
+    }
+
+    // Smallest rectangle covering both.
+    pub fn union(&self, other: GsnRect) -> GsnRect {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = (self.x + self.w as i32).max(other.x + other.w as i32);
+        let bottom = (self.y + self.h as i32).max(other.y + other.h as i32);
+        GsnRect::new(x, y, (right - x) as u32, (bottom - y) as u32)
+    }
+
+    pub fn area(&self) -> u64 {
+        self.w as u64 * self.h as u64
+    }
+}
+
+impl GsnColorKey {
+    fn skips(&self, p: Pixel) -> bool {
+        match *self {
+            GsnColorKey::None => false,
+            GsnColorKey::Color(key) => p == key,
+            GsnColorKey::Alpha(min) => p.a() < min
+        }
+    }
+}
+
+impl GsnSprite {
+    pub fn draw_sprite(&mut self, sprite: &GsnSprite, x: i32, y: i32) {
+        self.draw_sprite_ex(sprite, x, y, GsnBlit::default());
+    }
+
+    pub fn draw_sprite_ex(&mut self, sprite: &GsnSprite, x: i32, y: i32, blit: GsnBlit) {
+        let src = GsnRect::new(0, 0, sprite.width, sprite.height);
+        self.draw_partial_sprite_ex(sprite, src, x, y, blit);
+    }
+
+    pub fn draw_partial_sprite(&mut self, sprite: &GsnSprite, src: GsnRect, x: i32, y: i32) {
+        self.draw_partial_sprite_ex(sprite, src, x, y, GsnBlit::default());
+    }
+
+    // Copies the src rectangle of sprite so its top left lands on x, y.
+    // Flipping mirrors the rectangle in place, parts of src outside of sprite are treated as transparent.
+    pub fn draw_partial_sprite_ex(&mut self, sprite: &GsnSprite, src: GsnRect, x: i32, y: i32, blit: GsnBlit) {
+        let (x, y) = (x as i64, y as i64);
+        let dx0 = (-x).max(0);
+        let dy0 = (-y).max(0);
+        let dx1 = (src.w as i64).min(self.width as i64 - x);
+        let dy1 = (src.h as i64).min(self.height as i64 - y);
+
+        for dy in dy0..dy1 {
+            let sy = src.y as i64 + if blit.flip_y { src.h as i64 - 1 - dy } else { dy };
+            if sy < 0 || sy >= sprite.height as i64 {
+                continue;
+            }
+            for dx in dx0..dx1 {
+                let sx = src.x as i64 + if blit.flip_x { src.w as i64 - 1 - dx } else { dx };
+                if sx < 0 || sx >= sprite.width as i64 {
+                    continue;
+                }
+                let Some(p) = sprite.get_pixel(sx as u32, sy as u32) else { continue };
+                if blit.key.skips(p) {
+                    continue;
+                }
+                self.set_pixel((x + dx) as u32, (y + dy) as u32, p);
+            }
+        }
+    }
+}
@@ -0,0 +1,301 @@
+use crate::sprite::{GsnSprite, Pixel};
+
+// Shape primitives. Coordinates are signed so shapes can hang off any edge,
+// every pixel goes through the same clipping as fill_rect and is written at most once.
+impl GsnSprite {
+    pub fn draw_line(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, p: Pixel) {
+        let (w, h) = (self.width as i64, self.height as i64);
+        line_points(x0 as i64, y0 as i64, x1 as i64, y1 as i64, w, h, |x, y| self.plot(x, y, p));
+    }
+
+    pub fn draw_rect(&mut self, x: i32, y: i32, w: u32, h: u32, p: Pixel) {
+        if w == 0 || h == 0 {
+            return;
+        }
+        let (x0, y0) = (x as i64, y as i64);
+        let (x1, y1) = (x0 + w as i64 - 1, y0 + h as i64 - 1);
+        self.span(x0, x1, y0, p);
+        if y1 != y0 {
+            self.span(x0, x1, y1, p);
+        }
+        for dy in (y0 + 1)..y1 {
+            self.plot(x0, dy, p);
+            if x1 != x0 {
+                self.plot(x1, dy, p);
+            }
+        }
+    }
+
+    pub fn draw_circle(&mut self, x: i32, y: i32, r: u32, p: Pixel) {
+        let (x, y, r) = (x as i64, y as i64, r as i64);
+        if self.misses(x - r, y - r, x + r, y + r) {
+            return;
+        }
+        self.plot_outline(|emit| circle_points(x, y, r, emit), p);
+    }
+
+    pub fn fill_circle(&mut self, x: i32, y: i32, r: u32, p: Pixel) {
+        let (x, y, r) = (x as i64, y as i64, r as i64);
+        if self.misses(x - r, y - r, x + r, y + r) {
+            return;
+        }
+        self.fill_outline(|emit| circle_points(x, y, r, emit), p);
+    }
+
+    pub fn draw_ellipse(&mut self, x: i32, y: i32, rx: u32, ry: u32, p: Pixel) {
+        let (x, y, rx, ry) = (x as i64, y as i64, rx as i64, ry as i64);
+        if self.misses(x - rx, y - ry, x + rx, y + ry) {
+            return;
+        }
+        let (w, h) = (self.width as i64, self.height as i64);
+        self.plot_outline(|emit| ellipse_points(x, y, rx, ry, w, h, emit), p);
+    }
+
+    pub fn fill_ellipse(&mut self, x: i32, y: i32, rx: u32, ry: u32, p: Pixel) {
+        let (x, y, rx, ry) = (x as i64, y as i64, rx as i64, ry as i64);
+        if self.misses(x - rx, y - ry, x + rx, y + ry) {
+            return;
+        }
+        let (w, h) = (self.width as i64, self.height as i64);
+        self.fill_outline(|emit| ellipse_points(x, y, rx, ry, w, h, emit), p);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw_triangle(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, x2: i32, y2: i32, p: Pixel) {
+        let (x0, y0, x1, y1, x2, y2) = (x0 as i64, y0 as i64, x1 as i64, y1 as i64, x2 as i64, y2 as i64);
+        let (w, h) = (self.width as i64, self.height as i64);
+        // Corners are shared by two edges, plot_outline writes them once.
+        self.plot_outline(|emit| {
+            line_points(x0, y0, x1, y1, w, h, &mut *emit);
+            line_points(x1, y1, x2, y2, w, h, &mut *emit);
+            line_points(x2, y2, x0, y0, w, h, &mut *emit);
+        }, p);
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn fill_triangle(&mut self, x0: i32, y0: i32, x1: i32, y1: i32, x2: i32, y2: i32, p: Pixel) {
+        self.fill_polygon(&[(x0, y0), (x1, y1), (x2, y2)], p);
+    }
+
+    // Scanline fill with the non-zero winding rule, sampled at pixel centers.
+    // A pixel is covered when its center lies inside, or exactly on a left or top edge (top-left rule),
+    // so polygons sharing an edge never draw the same pixel twice and never leave a gap.
+    pub fn fill_polygon(&mut self, points: &[(i32, i32)], p: Pixel) {
+        if points.len() < 3 {
+            return;
+        }
+        let top = points.iter().map(|v| v.1 as i64).min().unwrap().max(0);
+        let bottom = points.iter().map(|v| v.1 as i64).max().unwrap().min(self.height as i64);
+
+        let mut crossings: Vec<(i64, i64)> = Vec::new();
+        for y in top..bottom {
+            crossings.clear();
+            for i in 0..points.len() {
+                let (ax, ay) = (points[i].0 as i64, points[i].1 as i64);
+                let (bx, by) = (points[(i + 1) % points.len()].0 as i64, points[(i + 1) % points.len()].1 as i64);
+                if ay == by {
+                    continue;
+                }
+                let ((ax, ay), (bx, by), winding) = if ay < by { ((ax, ay), (bx, by), 1) } else { ((bx, by), (ax, ay), -1) };
+                // Edges own their top row but not their bottom one.
+                if y < ay || y >= by {
+                    continue;
+                }
+                // The edge crosses the row center y + 0.5 at x = num / den,
+                // the first pixel whose center is right of it is ceil(x - 0.5).
+                let dy = (by - ay) as i128;
+                let num = 2 * ax as i128 * dy + (2 * (y - ay) + 1) as i128 * (bx - ax) as i128;
+                let den = 2 * dy;
+                let boundary = -((den - 2 * num).div_euclid(2 * den));
+                crossings.push((boundary as i64, winding));
+            }
+            crossings.sort_unstable();
+
+            let mut winding = 0;
+            for i in 0..crossings.len() {
+                winding += crossings[i].1;
+                if winding != 0 && i + 1 < crossings.len() {
+                    self.span(crossings[i].0, crossings[i + 1].0 - 1, y, p);
+                }
+            }
+        }
+    }
+
+    pub(crate) fn plot(&mut self, x: i64, y: i64, p: Pixel) {
+        if x >= 0 && y >= 0 && x < self.width as i64 && y < self.height as i64 {
+            self.set_pixel(x as u32, y as u32, p);
+        }
+    }
+
+    // Inclusive horizontal run from x0 to x1, clipped to the sprite.
+    pub(crate) fn span(&mut self, x0: i64, x1: i64, y: i64, p: Pixel) {
+        if y < 0 || y >= self.height as i64 {
+            return;
+        }
+        let x0 = x0.max(0);
+        let x1 = x1.min(self.width as i64 - 1);
+        for x in x0..=x1 {
+            self.set_pixel(x as u32, y as u32, p);
+        }
+    }
+
+    // True when the inclusive box x0,y0 - x1,y1 doesn't touch the sprite.
+    fn misses(&self, x0: i64, y0: i64, x1: i64, y1: i64) -> bool {
+        x1 < 0 || y1 < 0 || x0 >= self.width as i64 || y0 >= self.height as i64
+    }
+
+    // Outlines can visit a pixel more than once, only the visible ones are kept, then each is written once.
+    fn plot_outline<F: FnOnce(&mut dyn FnMut(i64, i64))>(&mut self, outline: F, p: Pixel) {
+        let (w, h) = (self.width as i64, self.height as i64);
+        let mut points = Vec::new();
+        outline(&mut |x, y| {
+            if x >= 0 && y >= 0 && x < w && y < h {
+                points.push((x, y));
+            }
+        });
+        points.sort_unstable();
+        points.dedup();
+        for (x, y) in points {
+            self.plot(x, y, p);
+        }
+    }
+
+    // The outlines are convex, so each visible row is filled between its leftmost and rightmost point.
+    fn fill_outline<F: FnOnce(&mut dyn FnMut(i64, i64))>(&mut self, outline: F, p: Pixel) {
+        let h = self.height as i64;
+        let mut rows: Vec<Option<(i64, i64)>> = vec![None; self.height as usize];
+        outline(&mut |x, y| {
+            if y >= 0 && y < h {
+                let row = &mut rows[y as usize];
+                *row = Some(row.map_or((x, x), |(x0, x1)| (x0.min(x), x1.max(x))));
+            }
+        });
+        for (y, row) in rows.into_iter().enumerate() {
+            if let Some((x0, x1)) = row {
+                self.span(x0, x1, y as i64, p);
+            }
+        }
+    }
+}
+
+// Bresenham. The major axis steps by one and the minor one is i * minor / major rounded to nearest,
+// halves rounding away from the start. The steps that land inside w x h are worked out first, so a line
+// running far off the sprite costs no more than the part that's visible.
+#[allow(clippy::too_many_arguments)]
+fn line_points(x0: i64, y0: i64, x1: i64, y1: i64, w: i64, h: i64, mut emit: impl FnMut(i64, i64)) {
+    let x_major = (x1 - x0).abs() >= (y1 - y0).abs();
+    // a is the major axis, b the minor one.
+    let (a0, b0, da, db, a_len, b_len) = if x_major {
+        (x0, y0, x1 - x0, y1 - y0, w, h)
+    } else {
+        (y0, x0, y1 - y0, x1 - x0, h, w)
+    };
+    let (n, m) = (da.abs() as i128, db.abs() as i128);
+    let (sa, sb) = (if da < 0 { -1 } else { 1 }, if db < 0 { -1 } else { 1 });
+    let point = |a: i64, b: i64| if x_major { (a, b) } else { (b, a) };
+
+    // Steps whose coordinate on an axis starting at c and moving by s lies in 0..len.
+    let inside = |c: i64, s: i64, len: i64| -> (i128, i128) {
+        let (c, len) = (c as i128, len as i128);
+        if s > 0 { (-c, len - 1 - c) } else { (c - len + 1, c) }
+    };
+    let (mut lo, mut hi) = inside(a0, sa, a_len);
+    lo = lo.max(0);
+    hi = hi.min(n);
+    let (k_lo, k_hi) = inside(b0, sb, b_len);
+    let (k_lo, k_hi) = (k_lo.max(0), k_hi.min(m));
+    if k_lo > k_hi {
+        return;
+    }
+    if m > 0 {
+        // The minor offset at step i is floor((2 i m + n) / 2n), solved for the first step reaching k_lo
+        // and the last one not passing k_hi.
+        let ceil_div = |a: i128, b: i128| -((-a).div_euclid(b));
+        lo = lo.max(ceil_div(2 * n * k_lo - n, 2 * m));
+        hi = hi.min(ceil_div(2 * n * (k_hi + 1) - n, 2 * m) - 1);
+    }
+    let mut i = lo;
+    while i <= hi {
+        let k = if n == 0 { 0 } else { (2 * i * m + n).div_euclid(2 * n) };
+        let (x, y) = point(a0 + sa * i as i64, b0 + sb * k as i64);
+        emit(x, y);
+        i += 1;
+    }
+}
+
+// Midpoint circle, one octant mirrored into the other seven.
+fn circle_points(cx: i64, cy: i64, r: i64, emit: &mut dyn FnMut(i64, i64)) {
+    let mut x = 0;
+    let mut y = r;
+    let mut d = 1 - r;
+    while x <= y {
+        for (px, py) in [(x, y), (y, x)] {
+            emit(cx + px, cy + py);
+            emit(cx - px, cy + py);
+            emit(cx + px, cy - py);
+            emit(cx - px, cy - py);
+        }
+        if d < 0 {
+            d += 2 * x + 3;
+        } else {
+            d += 2 * (x - y) + 5;
+            y -= 1;
+        }
+        x += 1;
+    }
+}
+
+// Midpoint ellipse, decision variables are kept multiplied by 4 so everything stays integer. They grow with
+// the fourth power of the radii, so they're i128 and radii past i32::MAX are drawn as i32::MAX.
+#[allow(clippy::too_many_arguments)]
+fn ellipse_points(cx: i64, cy: i64, rx: i64, ry: i64, w: i64, h: i64, emit: &mut dyn FnMut(i64, i64)) {
+    let (rx, ry) = (rx.min(i32::MAX as i64), ry.min(i32::MAX as i64));
+    // Flat ellipses are a line, only the part that can be seen is stepped through.
+    if rx == 0 || ry == 0 {
+        line_points(cx - rx, cy - ry, cx + rx, cy + ry, w, h, &mut *emit);
+        return;
+    }
+
+    let mut mirror = |x: i64, y: i64| {
+        emit(cx + x, cy + y);
+        emit(cx - x, cy + y);
+        emit(cx + x, cy - y);
+        emit(cx - x, cy - y);
+    };
+
+    let rx2 = rx as i128 * rx as i128;
+    let ry2 = ry as i128 * ry as i128;
+    let mut x = 0i64;
+    let mut y = ry;
+    let mut px = 0i128;
+    let mut py = 2 * rx2 * y as i128;
+
+    let mut d = 4 * ry2 - 4 * rx2 * ry as i128 + rx2;
+    while px < py {
+        mirror(x, y);
+        x += 1;
+        px += 2 * ry2;
+        if d < 0 {
+            d += 4 * (ry2 + px);
+        } else {
+            y -= 1;
+            py -= 2 * rx2;
+            d += 4 * (ry2 + px - py);
+        }
+    }
+
+    let (x1, y1) = (2 * x as i128 + 1, y as i128 - 1);
+    d = ry2 * x1 * x1 + 4 * rx2 * y1 * y1 - 4 * rx2 * ry2;
+    while y >= 0 {
+        mirror(x, y);
+        y -= 1;
+        py -= 2 * rx2;
+        if d > 0 {
+            d += 4 * (rx2 - py);
+        } else {
+            x += 1;
+            px += 2 * ry2;
+            d += 4 * (rx2 - py + px);
+        }
+    }
+}
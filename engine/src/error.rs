@@ -0,0 +1,48 @@
+use std::fmt;
+use std::io;
+
+#[derive(Debug)]
+pub enum GsnError {
+    Io(io::Error),
+    ImageDecode(String),
+    ImageEncode(String),
+    FontDecode(String),
+    InputMap(String),
+    // glfw or OpenGL couldn't be brought up, e.g. no display or no GL 3.3 driver.
+    ContextInit(String),
+    WindowCreation(String),
+    // The driver's info log.
+    ShaderCompile(String),
+    ShaderLink(String)
+}
+
+impl fmt::Display for GsnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GsnError::Io(e) => write!(f, "IO Error: {}", e),
+            GsnError::ImageDecode(e) => write!(f, "Image Decode Error: {}", e),
+            GsnError::ImageEncode(e) => write!(f, "Image Encode Error: {}", e),
+            GsnError::FontDecode(e) => write!(f, "Font Decode Error: {}", e),
+            GsnError::InputMap(e) => write!(f, "Input Map Error: {}", e),
+            GsnError::ContextInit(e) => write!(f, "Context Init Error: {}", e),
+            GsnError::WindowCreation(e) => write!(f, "Window Creation Error: {}", e),
+            GsnError::ShaderCompile(e) => write!(f, "Shader Compile Error: {}", e),
+            GsnError::ShaderLink(e) => write!(f, "Shader Link Error: {}", e)
+        }
+    }
+}
+
+impl std::error::Error for GsnError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GsnError::Io(e) => Some(e),
+            _ => None
+        }
+    }
+}
+
+impl From<io::Error> for GsnError {
+    fn from(e: io::Error) -> GsnError {
+        GsnError::Io(e)
+    }
+}
@@ -0,0 +1,127 @@
+use std::fs;
+use std::path::Path;
+use crate::error::GsnError;
+use crate::font::{GsnFont, GsnGlyph, MAX_GLYPH_SIZE};
+
+fn bdf_error(line: usize, message: &str) -> GsnError {
+    GsnError::FontDecode(format!("bdf line {}: {}", line + 1, message))
+}
+
+impl GsnFont {
+    pub fn load_bdf<P: AsRef<Path>>(path: P) -> Result<GsnFont, GsnError> {
+        GsnFont::from_bdf_bytes(&fs::read(path)?)
+    }
+
+    // Glyph ENCODING values are taken as Unicode code points, true for ISO10646 and ISO8859-1 fonts.
+    // DEFAULT_CHAR becomes the fallback glyph.
+    pub fn from_bdf_bytes(bytes: &[u8]) -> Result<GsnFont, GsnError> {
+        let text = String::from_utf8_lossy(bytes);
+        let mut lines = text.lines().enumerate().map(|(i, l)| (i, l.trim()));
+
+        let numbers = |i: usize, args: &[&str], count: usize| -> Result<Vec<i32>, GsnError> {
+            if args.len() < count {
+                return Err(bdf_error(i, "missing values"));
+            }
+            args[..count].iter().map(|a| a.parse::<i32>().map_err(|_| bdf_error(i, &format!("bad number {}", a)))).collect()
+        };
+
+        match lines.next() {
+            Some((_, l)) if l.starts_with("STARTFONT") => {}
+            _ => return Err(bdf_error(0, "missing STARTFONT"))
+        }
+
+        let mut bounding_box = [0i32; 4];
+        let mut ascent = None;
+        let mut descent = None;
+        let mut default_char = None;
+        let mut glyphs: Vec<(usize, i32, GsnGlyph)> = Vec::new();
+        let mut last = 0;
+
+        while let Some((i, line)) = lines.next() {
+            last = i;
+            let mut words = line.split_whitespace();
+            let keyword = words.next().unwrap_or("");
+            let args: Vec<&str> = words.collect();
+            match keyword {
+                "FONTBOUNDINGBOX" => bounding_box.copy_from_slice(&numbers(i, &args, 4)?),
+                "FONT_ASCENT" => ascent = Some(numbers(i, &args, 1)?[0]),
+                "FONT_DESCENT" => descent = Some(numbers(i, &args, 1)?[0]),
+                "DEFAULT_CHAR" => default_char = Some(numbers(i, &args, 1)?[0]),
+                "STARTCHAR" => {
+                    let start = i;
+                    let mut encoding = -1;
+                    let mut advance = None;
+                    let mut bbx = bounding_box;
+                    let mut rows = Vec::new();
+                    let mut row_count = 0;
+                    let mut in_bitmap = false;
+                    loop {
+                        let (i, line) = lines.next().ok_or_else(|| bdf_error(i, "missing ENDCHAR"))?;
+                        last = i;
+                        let mut words = line.split_whitespace();
+                        let keyword = words.next().unwrap_or("");
+                        let args: Vec<&str> = words.collect();
+                        match keyword {
+                            "ENDCHAR" => break,
+                            "ENCODING" => encoding = numbers(i, &args, 1)?[0],
+                            "DWIDTH" => advance = Some(numbers(i, &args, 1)?[0]),
+                            "BBX" => bbx.copy_from_slice(&numbers(i, &args, 4)?),
+                            "BITMAP" => in_bitmap = true,
+                            hex if in_bitmap => {
+                                row_count += 1;
+                                let row_bytes = (bbx[0].clamp(0, MAX_GLYPH_SIZE as i32) as usize).div_ceil(8);
+                                for b in 0..row_bytes {
+                                    let byte = hex.get(b * 2..b * 2 + 2).unwrap_or("00");
+                                    rows.push(u8::from_str_radix(byte, 16).map_err(|_| bdf_error(i, "bad bitmap row"))?);
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    if bbx[0] < 0 || bbx[1] < 0 {
+                        return Err(bdf_error(start, "negative glyph size"));
+                    }
+                    // One line per row, so glyph memory stays proportional to the file.
+                    if row_count != bbx[1] {
+                        return Err(bdf_error(start, &format!("{} bitmap rows for a glyph {} high", row_count, bbx[1])));
+                    }
+                    let mut glyph = GsnGlyph::from_packed_rows(bbx[0] as u32, bbx[1] as u32, &rows)
+                        .ok_or_else(|| bdf_error(start, &format!("{}x{} glyph is too large", bbx[0], bbx[1])))?;
+                    glyph.x_offset = bbx[2];
+                    // Until the ascent is known this is the distance from the baseline up to the top of the bitmap.
+                    glyph.y_offset = bbx[3].checked_add(bbx[1]).ok_or_else(|| bdf_error(start, "glyph offset out of range"))?;
+                    glyph.advance = advance.unwrap_or(bbx[0]).max(0) as u32;
+                    glyphs.push((start, encoding, glyph));
+                }
+                "ENDFONT" => break,
+                _ => {}
+            }
+        }
+
+        let out_of_range = || bdf_error(last, "font ascent or descent out of range");
+        let ascent = match ascent {
+            Some(ascent) => ascent,
+            None => bounding_box[1].checked_add(bounding_box[3]).ok_or_else(out_of_range)?
+        };
+        let descent = match descent {
+            Some(descent) => descent,
+            None => bounding_box[3].checked_neg().ok_or_else(out_of_range)?
+        };
+        let line_height = ascent.checked_add(descent).ok_or_else(out_of_range)?.max(1) as u32;
+        if line_height > MAX_GLYPH_SIZE {
+            return Err(bdf_error(last, &format!("line height {} is too large", line_height)));
+        }
+        let mut font = GsnFont::new(line_height);
+        for (line, encoding, mut glyph) in glyphs {
+            let Some(c) = u32::try_from(encoding).ok().and_then(char::from_u32) else {
+                continue;
+            };
+            glyph.y_offset = ascent.checked_sub(glyph.y_offset).ok_or_else(|| bdf_error(line, "glyph offset out of range"))?;
+            font.insert(c, glyph);
+        }
+        if let Some(c) = default_char.and_then(|d| u32::try_from(d).ok()).and_then(char::from_u32) {
+            font.set_fallback(c);
+        }
+        Ok(font)
+    }
+}
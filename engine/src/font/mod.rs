@@ -0,0 +1,296 @@
+mod bdf;
+mod psf;
+mod truetype;
+
+pub use truetype::{GsnCoverageGlyph, GsnTrueType};
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+use crate::error::GsnError;
+use crate::sprite::{GsnSprite, Pixel};
+
+const TAB_STOP: u32 = 4;
+// Largest width or height a glyph read from a font file may have.
+pub(crate) const MAX_GLYPH_SIZE: u32 = 1024;
+
+#[derive(Clone, Debug)]
+pub struct GsnGlyph {
+    width: u32,
+    height: u32,
+    // Where the bitmap's top left sits relative to the pen, which is at the top of the line.
+    pub x_offset: i32,
+    pub y_offset: i32,
+    pub advance: u32,
+    // width * height coverage, row by row.
+    bitmap: Vec<bool>
+}
+
+impl GsnGlyph {
+    // The bitmap has to hold exactly width * height entries, row by row. Offsets are 0 and the advance is the width.
+    pub fn new(width: u32, height: u32, bitmap: Vec<bool>) -> Result<GsnGlyph, GsnError> {
+        if bitmap.len() as u64 != width as u64 * height as u64 {
+            return Err(GsnError::FontDecode(format!("expected {} bits for a {}x{} glyph, got {}", width as u64 * height as u64, width, height, bitmap.len())));
+        }
+        Ok(GsnGlyph { width, height, x_offset: 0, y_offset: 0, advance: width, bitmap })
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    // False outside the bitmap.
+    pub fn get(&self, x: u32, y: u32) -> bool {
+        x < self.width && y < self.height && self.bitmap[y as usize * self.width as usize + x as usize]
+    }
+
+    // Hollow box, the last resort for characters a font has no glyph for.
+    fn missing(line_height: u32) -> GsnGlyph {
+        let line_height = line_height.min(MAX_GLYPH_SIZE);
+        let (width, height) = ((line_height / 2).max(3), line_height.max(3));
+        let bitmap = (0..height).flat_map(|y| (0..width).map(move |x| {
+            x == 0 || y == 0 || x == width - 1 || y == height - 1
+        })).collect();
+        GsnGlyph { width, height, x_offset: 0, y_offset: 0, advance: width + 1, bitmap }
+    }
+
+    // Unpacks rows of MSB first bits, each row padded to whole bytes, as used by BDF and PSF.
+    // None when either side is over MAX_GLYPH_SIZE.
+    pub(crate) fn from_packed_rows(width: u32, height: u32, rows: &[u8]) -> Option<GsnGlyph> {
+        if width > MAX_GLYPH_SIZE || height > MAX_GLYPH_SIZE {
+            return None;
+        }
+        let stride = width.div_ceil(8) as usize;
+        let bitmap = (0..height as usize).flat_map(|y| (0..width as usize).map(move |x| {
+            rows.get(y * stride + x / 8).is_some_and(|b| b & (0x80 >> (x % 8)) != 0)
+        })).collect();
+        Some(GsnGlyph { width, height, x_offset: 0, y_offset: 0, advance: width, bitmap })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GsnFont {
+    glyphs: HashMap<char, GsnGlyph>,
+    line_height: u32,
+    fallback: char,
+    missing: GsnGlyph
+}
+
+impl GsnFont {
+    pub fn new(line_height: u32) -> GsnFont {
+        GsnFont {
+            glyphs: HashMap::new(),
+            line_height,
+            fallback: '?',
+            missing: GsnGlyph::missing(line_height)
+        }
+    }
+
+    // The 8x8 font from the IBM PC BIOS, printable ASCII only.
+    pub fn builtin() -> &'static GsnFont {
+        static BUILTIN: OnceLock<GsnFont> = OnceLock::new();
+        BUILTIN.get_or_init(|| {
+            let mut font = GsnFont::new(8);
+            for (i, rows) in FONT_8X8.iter().enumerate() {
+                let bitmap = rows.iter().flat_map(|row| (0..8).map(move |x| row >> x & 1 != 0)).collect();
+                let glyph = GsnGlyph { width: 8, height: 8, x_offset: 0, y_offset: 0, advance: 8, bitmap };
+                font.insert(char::from(b' ' + i as u8), glyph);
+            }
+            font
+        })
+    }
+
+    pub fn insert(&mut self, c: char, glyph: GsnGlyph) {
+        self.glyphs.insert(c, glyph);
+    }
+
+    pub fn line_height(&self) -> u32 {
+        self.line_height
+    }
+
+    pub fn set_fallback(&mut self, c: char) {
+        self.fallback = c;
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        self.glyphs.contains_key(&c)
+    }
+
+    pub fn glyph_count(&self) -> usize {
+        self.glyphs.len()
+    }
+
+    // Characters the font doesn't have come back as the fallback glyph, then U+FFFD, then a hollow box.
+    pub fn glyph(&self, c: char) -> &GsnGlyph {
+        self.glyphs.get(&c)
+            .or_else(|| self.glyphs.get(&self.fallback))
+            .or_else(|| self.glyphs.get(&char::REPLACEMENT_CHARACTER))
+            .unwrap_or(&self.missing)
+    }
+
+    pub fn text_size(&self, text: &str, scale: u32) -> (u32, u32) {
+        self.layout(text, scale, |_, _, _| {})
+    }
+
+    // Walks text calling place(glyph, x, y) with every pen position, returns the size of the block of text.
+    // Huge scales saturate rather than wrap, a block too big for u32 reports u32::MAX.
+    fn layout<F: FnMut(&GsnGlyph, i64, i64)>(&self, text: &str, scale: u32, mut place: F) -> (u32, u32) {
+        let scale = scale.max(1) as i64;
+        let line_height = (self.line_height as i64).saturating_mul(scale);
+        let tab = (self.glyph(' ').advance as i64 * TAB_STOP as i64).saturating_mul(scale);
+        let (mut x, mut y, mut width) = (0i64, 0i64, 0i64);
+        for c in text.chars() {
+            match c {
+                '\n' => {
+                    x = 0;
+                    y = y.saturating_add(line_height);
+                }
+                '\t' => x = (x / tab.max(1) + 1).saturating_mul(tab),
+                '\r' => {}
+                _ => {
+                    let glyph = self.glyph(c);
+                    place(glyph, x, y);
+                    x = x.saturating_add((glyph.advance as i64).saturating_mul(scale));
+                }
+            }
+            width = width.max(x);
+        }
+        if text.is_empty() {
+            return (0, 0);
+        }
+        (u32::try_from(width).unwrap_or(u32::MAX), u32::try_from(y.saturating_add(line_height)).unwrap_or(u32::MAX))
+    }
+}
+
+// Size of text drawn with draw_string.
+pub fn text_size(text: &str, scale: u32) -> (u32, u32) {
+    GsnFont::builtin().text_size(text, scale)
+}
+
+impl GsnSprite {
+    // Built in font, newlines return to x, tabs snap to every 4th character and scale blows every pixel up to a square.
+    pub fn draw_string(&mut self, x: i32, y: i32, text: &str, color: Pixel, scale: u32) {
+        self.draw_text(GsnFont::builtin(), x, y, text, color, scale);
+    }
+
+    pub fn draw_text(&mut self, font: &GsnFont, x: i32, y: i32, text: &str, color: Pixel, scale: u32) {
+        let scale = scale.max(1) as i64;
+        font.layout(text, scale as u32, |glyph, pen_x, pen_y| {
+            let left = x as i64 + pen_x + glyph.x_offset as i64 * scale;
+            let top = y as i64 + pen_y + glyph.y_offset as i64 * scale;
+            for gy in 0..glyph.height as i64 {
+                for gx in 0..glyph.width as i64 {
+                    if !glyph.bitmap[(gy * glyph.width as i64 + gx) as usize] {
+                        continue;
+                    }
+                    for sy in 0..scale {
+                        let px = left + gx * scale;
+                        self.span(px, px + scale - 1, top + gy * scale + sy, color);
+                    }
+                }
+            }
+        });
+    }
+}
+
+// Bit 0 is the leftmost pixel of a row.
+const FONT_8X8: [[u8; 8]; 95] = [
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // ' '
+    [0x18, 0x3C, 0x3C, 0x18, 0x18, 0x00, 0x18, 0x00], // '!'
+    [0x36, 0x36, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '"'
+    [0x36, 0x36, 0x7F, 0x36, 0x7F, 0x36, 0x36, 0x00], // '#'
+    [0x0C, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x0C, 0x00], // '$'
+    [0x00, 0x63, 0x33, 0x18, 0x0C, 0x66, 0x63, 0x00], // '%'
+    [0x1C, 0x36, 0x1C, 0x6E, 0x3B, 0x33, 0x6E, 0x00], // '&'
+    [0x06, 0x06, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00], // "'"
+    [0x18, 0x0C, 0x06, 0x06, 0x06, 0x0C, 0x18, 0x00], // '('
+    [0x06, 0x0C, 0x18, 0x18, 0x18, 0x0C, 0x06, 0x00], // ')'
+    [0x00, 0x66, 0x3C, 0xFF, 0x3C, 0x66, 0x00, 0x00], // '*'
+    [0x00, 0x0C, 0x0C, 0x3F, 0x0C, 0x0C, 0x00, 0x00], // '+'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ','
+    [0x00, 0x00, 0x00, 0x3F, 0x00, 0x00, 0x00, 0x00], // '-'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x0C, 0x0C, 0x00], // '.'
+    [0x60, 0x30, 0x18, 0x0C, 0x06, 0x03, 0x01, 0x00], // '/'
+    [0x3E, 0x63, 0x73, 0x7B, 0x6F, 0x67, 0x3E, 0x00], // '0'
+    [0x0C, 0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x3F, 0x00], // '1'
+    [0x1E, 0x33, 0x30, 0x1C, 0x06, 0x33, 0x3F, 0x00], // '2'
+    [0x1E, 0x33, 0x30, 0x1C, 0x30, 0x33, 0x1E, 0x00], // '3'
+    [0x38, 0x3C, 0x36, 0x33, 0x7F, 0x30, 0x78, 0x00], // '4'
+    [0x3F, 0x03, 0x1F, 0x30, 0x30, 0x33, 0x1E, 0x00], // '5'
+    [0x1C, 0x06, 0x03, 0x1F, 0x33, 0x33, 0x1E, 0x00], // '6'
+    [0x3F, 0x33, 0x30, 0x18, 0x0C, 0x0C, 0x0C, 0x00], // '7'
+    [0x1E, 0x33, 0x33, 0x1E, 0x33, 0x33, 0x1E, 0x00], // '8'
+    [0x1E, 0x33, 0x33, 0x3E, 0x30, 0x18, 0x0E, 0x00], // '9'
+    [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x00], // ':'
+    [0x00, 0x0C, 0x0C, 0x00, 0x00, 0x0C, 0x0C, 0x06], // ';'
+    [0x18, 0x0C, 0x06, 0x03, 0x06, 0x0C, 0x18, 0x00], // '<'
+    [0x00, 0x00, 0x3F, 0x00, 0x00, 0x3F, 0x00, 0x00], // '='
+    [0x06, 0x0C, 0x18, 0x30, 0x18, 0x0C, 0x06, 0x00], // '>'
+    [0x1E, 0x33, 0x30, 0x18, 0x0C, 0x00, 0x0C, 0x00], // '?'
+    [0x3E, 0x63, 0x7B, 0x7B, 0x7B, 0x03, 0x1E, 0x00], // '@'
+    [0x0C, 0x1E, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x00], // 'A'
+    [0x3F, 0x66, 0x66, 0x3E, 0x66, 0x66, 0x3F, 0x00], // 'B'
+    [0x3C, 0x66, 0x03, 0x03, 0x03, 0x66, 0x3C, 0x00], // 'C'
+    [0x1F, 0x36, 0x66, 0x66, 0x66, 0x36, 0x1F, 0x00], // 'D'
+    [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x46, 0x7F, 0x00], // 'E'
+    [0x7F, 0x46, 0x16, 0x1E, 0x16, 0x06, 0x0F, 0x00], // 'F'
+    [0x3C, 0x66, 0x03, 0x03, 0x73, 0x66, 0x7C, 0x00], // 'G'
+    [0x33, 0x33, 0x33, 0x3F, 0x33, 0x33, 0x33, 0x00], // 'H'
+    [0x1E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'I'
+    [0x78, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E, 0x00], // 'J'
+    [0x67, 0x66, 0x36, 0x1E, 0x36, 0x66, 0x67, 0x00], // 'K'
+    [0x0F, 0x06, 0x06, 0x06, 0x46, 0x66, 0x7F, 0x00], // 'L'
+    [0x63, 0x77, 0x7F, 0x7F, 0x6B, 0x63, 0x63, 0x00], // 'M'
+    [0x63, 0x67, 0x6F, 0x7B, 0x73, 0x63, 0x63, 0x00], // 'N'
+    [0x1C, 0x36, 0x63, 0x63, 0x63, 0x36, 0x1C, 0x00], // 'O'
+    [0x3F, 0x66, 0x66, 0x3E, 0x06, 0x06, 0x0F, 0x00], // 'P'
+    [0x1E, 0x33, 0x33, 0x33, 0x3B, 0x1E, 0x38, 0x00], // 'Q'
+    [0x3F, 0x66, 0x66, 0x3E, 0x36, 0x66, 0x67, 0x00], // 'R'
+    [0x1E, 0x33, 0x07, 0x0E, 0x38, 0x33, 0x1E, 0x00], // 'S'
+    [0x3F, 0x2D, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'T'
+    [0x33, 0x33, 0x33, 0x33, 0x33, 0x33, 0x3F, 0x00], // 'U'
+    [0x33, 0x33, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // 'V'
+    [0x63, 0x63, 0x63, 0x6B, 0x7F, 0x77, 0x63, 0x00], // 'W'
+    [0x63, 0x63, 0x36, 0x1C, 0x1C, 0x36, 0x63, 0x00], // 'X'
+    [0x33, 0x33, 0x33, 0x1E, 0x0C, 0x0C, 0x1E, 0x00], // 'Y'
+    [0x7F, 0x63, 0x31, 0x18, 0x4C, 0x66, 0x7F, 0x00], // 'Z'
+    [0x1E, 0x06, 0x06, 0x06, 0x06, 0x06, 0x1E, 0x00], // '['
+    [0x03, 0x06, 0x0C, 0x18, 0x30, 0x60, 0x40, 0x00], // '\\'
+    [0x1E, 0x18, 0x18, 0x18, 0x18, 0x18, 0x1E, 0x00], // ']'
+    [0x08, 0x1C, 0x36, 0x63, 0x00, 0x00, 0x00, 0x00], // '^'
+    [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xFF], // '_'
+    [0x0C, 0x0C, 0x18, 0x00, 0x00, 0x00, 0x00, 0x00], // '`'
+    [0x00, 0x00, 0x1E, 0x30, 0x3E, 0x33, 0x6E, 0x00], // 'a'
+    [0x07, 0x06, 0x06, 0x3E, 0x66, 0x66, 0x3B, 0x00], // 'b'
+    [0x00, 0x00, 0x1E, 0x33, 0x03, 0x33, 0x1E, 0x00], // 'c'
+    [0x38, 0x30, 0x30, 0x3E, 0x33, 0x33, 0x6E, 0x00], // 'd'
+    [0x00, 0x00, 0x1E, 0x33, 0x3F, 0x03, 0x1E, 0x00], // 'e'
+    [0x1C, 0x36, 0x06, 0x0F, 0x06, 0x06, 0x0F, 0x00], // 'f'
+    [0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x1F], // 'g'
+    [0x07, 0x06, 0x36, 0x6E, 0x66, 0x66, 0x67, 0x00], // 'h'
+    [0x0C, 0x00, 0x0E, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'i'
+    [0x30, 0x00, 0x30, 0x30, 0x30, 0x33, 0x33, 0x1E], // 'j'
+    [0x07, 0x06, 0x66, 0x36, 0x1E, 0x36, 0x67, 0x00], // 'k'
+    [0x0E, 0x0C, 0x0C, 0x0C, 0x0C, 0x0C, 0x1E, 0x00], // 'l'
+    [0x00, 0x00, 0x33, 0x7F, 0x7F, 0x6B, 0x63, 0x00], // 'm'
+    [0x00, 0x00, 0x1F, 0x33, 0x33, 0x33, 0x33, 0x00], // 'n'
+    [0x00, 0x00, 0x1E, 0x33, 0x33, 0x33, 0x1E, 0x00], // 'o'
+    [0x00, 0x00, 0x3B, 0x66, 0x66, 0x3E, 0x06, 0x0F], // 'p'
+    [0x00, 0x00, 0x6E, 0x33, 0x33, 0x3E, 0x30, 0x78], // 'q'
+    [0x00, 0x00, 0x3B, 0x6E, 0x66, 0x06, 0x0F, 0x00], // 'r'
+    [0x00, 0x00, 0x3E, 0x03, 0x1E, 0x30, 0x1F, 0x00], // 's'
+    [0x08, 0x0C, 0x3E, 0x0C, 0x0C, 0x2C, 0x18, 0x00], // 't'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x33, 0x6E, 0x00], // 'u'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x1E, 0x0C, 0x00], // 'v'
+    [0x00, 0x00, 0x63, 0x6B, 0x7F, 0x7F, 0x36, 0x00], // 'w'
+    [0x00, 0x00, 0x63, 0x36, 0x1C, 0x36, 0x63, 0x00], // 'x'
+    [0x00, 0x00, 0x33, 0x33, 0x33, 0x3E, 0x30, 0x1F], // 'y'
+    [0x00, 0x00, 0x3F, 0x19, 0x0C, 0x26, 0x3F, 0x00], // 'z'
+    [0x38, 0x0C, 0x0C, 0x07, 0x0C, 0x0C, 0x38, 0x00], // '{'
+    [0x18, 0x18, 0x18, 0x00, 0x18, 0x18, 0x18, 0x00], // '|'
+    [0x07, 0x0C, 0x0C, 0x38, 0x0C, 0x0C, 0x07, 0x00], // '}'
+    [0x6E, 0x3B, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00], // '~'
+];
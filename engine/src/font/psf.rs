@@ -0,0 +1,117 @@
+use std::fs;
+use std::path::Path;
+use crate::error::GsnError;
+use crate::font::{GsnFont, GsnGlyph};
+
+const PSF1_MAGIC: [u8; 2] = [0x36, 0x04];
+const PSF1_MODE512: u8 = 0x01;
+const PSF1_MODEHASTAB: u8 = 0x02;
+const PSF1_SEPARATOR: u16 = 0xffff;
+const PSF1_STARTSEQ: u16 = 0xfffe;
+const PSF2_MAGIC: [u8; 4] = [0x72, 0xb5, 0x4a, 0x86];
+const PSF2_HAS_UNICODE_TABLE: u32 = 0x01;
+const PSF2_SEPARATOR: u8 = 0xff;
+const PSF2_STARTSEQ: u8 = 0xfe;
+
+fn psf_error(message: &str) -> GsnError {
+    GsnError::FontDecode(format!("psf: {}", message))
+}
+
+fn u32_at(bytes: &[u8], i: usize) -> Result<u32, GsnError> {
+    bytes.get(i..i + 4).map(|b| u32::from_le_bytes([b[0], b[1], b[2], b[3]])).ok_or_else(|| psf_error("truncated header"))
+}
+
+impl GsnFont {
+    pub fn load_psf<P: AsRef<Path>>(path: P) -> Result<GsnFont, GsnError> {
+        GsnFont::from_psf_bytes(&fs::read(path)?)
+    }
+
+    // PC Screen Font, version 1 or 2. With a unicode table every code point listed for a glyph maps to it,
+    // without one glyph n is code point n. Combining sequences in the table are skipped.
+    pub fn from_psf_bytes(bytes: &[u8]) -> Result<GsnFont, GsnError> {
+        if bytes.starts_with(&PSF1_MAGIC) {
+            let mode = *bytes.get(2).ok_or_else(|| psf_error("truncated header"))?;
+            let height = *bytes.get(3).ok_or_else(|| psf_error("truncated header"))? as u32;
+            let count = if mode & PSF1_MODE512 != 0 { 512 } else { 256 };
+            if height == 0 {
+                return Err(psf_error("glyph size 0"));
+            }
+            let glyphs = glyph_table(bytes, 4, count, height as usize, 8, height)?;
+
+            let mut table: Vec<Vec<char>> = vec![vec![]; count];
+            if mode & PSF1_MODEHASTAB != 0 {
+                let mut entries = bytes[4 + count * height as usize..].chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]]));
+                for chars in table.iter_mut() {
+                    let mut in_sequence = false;
+                    for entry in entries.by_ref() {
+                        match entry {
+                            PSF1_SEPARATOR => break,
+                            PSF1_STARTSEQ => in_sequence = true,
+                            _ if in_sequence => {}
+                            _ => chars.extend(char::from_u32(entry as u32))
+                        }
+                    }
+                }
+            }
+            Ok(build(glyphs, table, height))
+        } else if bytes.starts_with(&PSF2_MAGIC) {
+            let header_size = u32_at(bytes, 8)? as usize;
+            let flags = u32_at(bytes, 12)?;
+            let count = u32_at(bytes, 16)? as usize;
+            let glyph_size = u32_at(bytes, 20)? as usize;
+            let height = u32_at(bytes, 24)?;
+            let width = u32_at(bytes, 28)?;
+            if glyph_size == 0 {
+                return Err(psf_error("glyph size 0"));
+            }
+            if glyph_size < width.div_ceil(8) as usize * height as usize {
+                return Err(psf_error("glyph size too small for its dimensions"));
+            }
+            // The count comes straight from the header, it can't be more than the glyphs the file has room for.
+            if count > bytes.len().saturating_sub(header_size) / glyph_size {
+                return Err(psf_error("truncated glyph table"));
+            }
+            let glyphs = glyph_table(bytes, header_size, count, glyph_size, width, height)?;
+
+            let mut table: Vec<Vec<char>> = vec![vec![]; count];
+            if flags & PSF2_HAS_UNICODE_TABLE != 0 {
+                let mut rest = &bytes[header_size + count * glyph_size..];
+                for chars in table.iter_mut() {
+                    let end = rest.iter().position(|&b| b == PSF2_SEPARATOR).unwrap_or(rest.len());
+                    let entry = &rest[..end];
+                    let singles = entry.split(|&b| b == PSF2_STARTSEQ).next().unwrap_or(&[]);
+                    if let Ok(singles) = std::str::from_utf8(singles) {
+                        chars.extend(singles.chars());
+                    }
+                    rest = rest.get(end + 1..).unwrap_or(&[]);
+                }
+            }
+            Ok(build(glyphs, table, height))
+        } else {
+            Err(psf_error("missing PSF1 or PSF2 magic"))
+        }
+    }
+}
+
+fn glyph_table(bytes: &[u8], offset: usize, count: usize, glyph_size: usize, width: u32, height: u32) -> Result<Vec<GsnGlyph>, GsnError> {
+    let end = count.checked_mul(glyph_size).and_then(|n| n.checked_add(offset)).ok_or_else(|| psf_error("glyph table too large"))?;
+    let data = bytes.get(offset..end).ok_or_else(|| psf_error("truncated glyph table"))?;
+    data.chunks_exact(glyph_size).take(count)
+        .map(|rows| GsnGlyph::from_packed_rows(width, height, rows).ok_or_else(|| psf_error(&format!("{}x{} glyphs are too large", width, height))))
+        .collect()
+}
+
+fn build(glyphs: Vec<GsnGlyph>, table: Vec<Vec<char>>, height: u32) -> GsnFont {
+    let mut font = GsnFont::new(height);
+    let has_table = table.iter().any(|chars| !chars.is_empty());
+    for (i, (glyph, chars)) in glyphs.into_iter().zip(table).enumerate() {
+        if has_table {
+            for c in chars {
+                font.insert(c, glyph.clone());
+            }
+        } else if let Some(c) = char::from_u32(i as u32) {
+            font.insert(c, glyph);
+        }
+    }
+    font
+}
@@ -0,0 +1,619 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use crate::blend::GsnBlendMode;
+use crate::error::GsnError;
+use crate::font::TAB_STOP;
+use crate::sprite::{pixel_rgba, GsnSprite, Pixel};
+
+const ON_CURVE: u8 = 0x01;
+const X_SHORT: u8 = 0x02;
+const Y_SHORT: u8 = 0x04;
+const REPEAT: u8 = 0x08;
+const X_SAME_OR_POSITIVE: u8 = 0x10;
+const Y_SAME_OR_POSITIVE: u8 = 0x20;
+
+const ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const ARGS_ARE_XY_VALUES: u16 = 0x0002;
+const WE_HAVE_A_SCALE: u16 = 0x0008;
+const MORE_COMPONENTS: u16 = 0x0020;
+const WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+const MAX_COMPONENT_DEPTH: u32 = 8;
+// Components across the whole tree of one glyph, nesting multiplies them.
+const MAX_COMPONENTS: u32 = 1024;
+// Glyphs wider or taller than this many pixels are left empty.
+const MAX_RASTER_SIZE: f32 = 4096.0;
+
+// Points in font units, y up, each flagged on or off the curve.
+type Contour = Vec<(f32, f32, bool)>;
+
+fn ttf_error(message: &str) -> GsnError {
+    GsnError::FontDecode(format!("ttf: {}", message))
+}
+
+fn bytes_at<const N: usize>(data: &[u8], at: usize) -> Result<[u8; N], GsnError> {
+    let mut out = [0; N];
+    out.copy_from_slice(data.get(at..at.saturating_add(N)).ok_or_else(|| ttf_error("unexpected end of file"))?);
+    Ok(out)
+}
+
+fn u16_at(data: &[u8], at: usize) -> Result<u16, GsnError> {
+    Ok(u16::from_be_bytes(bytes_at(data, at)?))
+}
+
+fn i16_at(data: &[u8], at: usize) -> Result<i16, GsnError> {
+    Ok(i16::from_be_bytes(bytes_at(data, at)?))
+}
+
+fn u32_at(data: &[u8], at: usize) -> Result<u32, GsnError> {
+    Ok(u32::from_be_bytes(bytes_at(data, at)?))
+}
+
+// 2.14 fixed point, used by composite glyph transforms.
+fn f2dot14_at(data: &[u8], at: usize) -> Result<f32, GsnError> {
+    Ok(i16_at(data, at)? as f32 / 16384.0)
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct GsnCoverageGlyph {
+    pub width: u32,
+    pub height: u32,
+    // Where the bitmap's top left sits relative to the pen, which is at the top of the line.
+    pub x_offset: i32,
+    pub y_offset: i32,
+    pub advance: f32,
+    // width * height coverage from 0 to 255, row by row.
+    pub coverage: Vec<u8>
+}
+
+// Scalable font with TrueType outlines, from a .ttf, a glyf flavoured .otf or the first face of a .ttc.
+// Sizes are the em size in pixels. Rasterized glyphs are cached per character and size.
+#[derive(Clone, Debug)]
+pub struct GsnTrueType {
+    data: Vec<u8>,
+    units_per_em: f32,
+    num_glyphs: u16,
+    long_loca: bool,
+    ascent: f32,
+    descent: f32,
+    line_gap: f32,
+    h_metrics: u16,
+    cmap: usize,
+    cmap_format: u16,
+    loca: usize,
+    glyf: usize,
+    hmtx: usize,
+    cache: HashMap<(char, u32), GsnCoverageGlyph>
+}
+
+impl GsnTrueType {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<GsnTrueType, GsnError> {
+        GsnTrueType::from_bytes(fs::read(path)?)
+    }
+
+    pub fn from_bytes(data: Vec<u8>) -> Result<GsnTrueType, GsnError> {
+        let mut offset = 0;
+        match &bytes_at::<4>(&data, 0)? {
+            b"ttcf" => offset = u32_at(&data, 12)? as usize,
+            b"OTTO" => return Err(ttf_error("CFF outlines are not supported")),
+            _ => {}
+        }
+        let version = u32_at(&data, offset)?;
+        if version != 0x0001_0000 && &version.to_be_bytes() != b"true" {
+            return Err(ttf_error("not a TrueType font"));
+        }
+
+        let table_count = u16_at(&data, offset + 4)? as usize;
+        let mut tables = HashMap::new();
+        for i in 0..table_count {
+            let record = offset + 12 + i * 16;
+            tables.insert(bytes_at::<4>(&data, record)?, u32_at(&data, record + 8)? as usize);
+        }
+        let table = |tag: &[u8; 4]| -> Result<usize, GsnError> {
+            tables.get(tag).copied().ok_or_else(|| ttf_error(&format!("missing {} table", String::from_utf8_lossy(tag))))
+        };
+        let (head, maxp, hhea, cmap) = (table(b"head")?, table(b"maxp")?, table(b"hhea")?, table(b"cmap")?);
+        let (loca, glyf, hmtx) = (table(b"loca")?, table(b"glyf")?, table(b"hmtx")?);
+
+        let units_per_em = u16_at(&data, head + 18)?;
+        if units_per_em == 0 {
+            return Err(ttf_error("zero units per em"));
+        }
+
+        // Prefer a full unicode map (format 12), then the BMP (format 4), then a symbol font's map.
+        let mut best = None;
+        for i in 0..u16_at(&data, cmap + 2)? as usize {
+            let record = cmap + 4 + i * 8;
+            let platform = u16_at(&data, record)?;
+            let encoding = u16_at(&data, record + 2)?;
+            let subtable = cmap + u32_at(&data, record + 4)? as usize;
+            let format = u16_at(&data, subtable)?;
+            let unicode = platform == 0 || (platform == 3 && (encoding == 1 || encoding == 10));
+            let rank = match format {
+                12 if unicode => 3,
+                4 if unicode => 2,
+                4 if platform == 3 && encoding == 0 => 1,
+                _ => continue
+            };
+            if best.is_none_or(|(r, _, _)| rank > r) {
+                best = Some((rank, subtable, format));
+            }
+        }
+        let (_, cmap, cmap_format) = best.ok_or_else(|| ttf_error("no usable character map"))?;
+
+        Ok(GsnTrueType {
+            units_per_em: units_per_em as f32,
+            num_glyphs: u16_at(&data, maxp + 4)?,
+            long_loca: i16_at(&data, head + 50)? != 0,
+            ascent: i16_at(&data, hhea + 4)? as f32,
+            descent: i16_at(&data, hhea + 6)? as f32,
+            line_gap: i16_at(&data, hhea + 8)? as f32,
+            h_metrics: u16_at(&data, hhea + 34)?,
+            cmap,
+            cmap_format,
+            loca,
+            glyf,
+            hmtx,
+            cache: HashMap::new(),
+            data
+        })
+    }
+
+    pub fn contains(&self, c: char) -> bool {
+        self.glyph_index(c) != 0
+    }
+
+    pub fn glyph_count(&self) -> usize {
+        self.num_glyphs as usize
+    }
+
+    pub fn line_height(&self, size: u32) -> u32 {
+        ((self.ascent - self.descent + self.line_gap) * self.scale(size)).ceil() as u32
+    }
+
+    pub fn cached_glyphs(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+
+    pub fn text_size(&mut self, text: &str, size: u32) -> (u32, u32) {
+        self.layout(text, size, |_, _, _| {})
+    }
+
+    // Rasterizes on first use, characters without a glyph get the font's .notdef.
+    pub fn glyph(&mut self, c: char, size: u32) -> &GsnCoverageGlyph {
+        if !self.cache.contains_key(&(c, size)) {
+            let glyph = self.rasterize(c, size);
+            self.cache.insert((c, size), glyph);
+        }
+        &self.cache[&(c, size)]
+    }
+
+    fn scale(&self, size: u32) -> f32 {
+        size as f32 / self.units_per_em
+    }
+
+    // Same rules as GsnFont, the pen advances by fractional amounts but glyphs land on whole pixels.
+    fn layout<F: FnMut(&GsnCoverageGlyph, i64, i64)>(&mut self, text: &str, size: u32, mut place: F) -> (u32, u32) {
+        let line_height = self.line_height(size) as i64;
+        let tab = self.glyph(' ', size).advance * TAB_STOP as f32;
+        let (mut x, mut y, mut width) = (0f32, 0i64, 0f32);
+        for c in text.chars() {
+            match c {
+                '\n' => {
+                    x = 0.0;
+                    y += line_height;
+                }
+                '\t' => x = if tab > 0.0 { ((x / tab).floor() + 1.0) * tab } else { x },
+                '\r' => {}
+                _ => {
+                    let glyph = self.glyph(c, size);
+                    place(glyph, x.round() as i64, y);
+                    x += glyph.advance;
+                }
+            }
+            width = width.max(x);
+        }
+        if text.is_empty() {
+            return (0, 0);
+        }
+        (width.ceil() as u32, u32::try_from(y + line_height).unwrap_or(u32::MAX))
+    }
+
+    fn glyph_index(&self, c: char) -> u16 {
+        let c = c as u32;
+        let data = &self.data;
+        let lookup = || -> Result<u16, GsnError> {
+            if self.cmap_format == 12 {
+                let groups = u32_at(data, self.cmap + 12)? as usize;
+                let (mut lo, mut hi) = (0, groups);
+                while lo < hi {
+                    let mid = (lo + hi) / 2;
+                    let group = self.cmap + 16 + mid * 12;
+                    if c < u32_at(data, group)? {
+                        hi = mid;
+                    } else if c > u32_at(data, group + 4)? {
+                        lo = mid + 1;
+                    } else {
+                        let glyph = u32_at(data, group + 8)?.checked_add(c - u32_at(data, group)?);
+                        return Ok(glyph.and_then(|g| u16::try_from(g).ok()).unwrap_or(0));
+                    }
+                }
+                return Ok(0);
+            }
+
+            if c > 0xffff {
+                return Ok(0);
+            }
+            let seg_x2 = u16_at(data, self.cmap + 6)? as usize;
+            let ends = self.cmap + 14;
+            let starts = ends + seg_x2 + 2;
+            let deltas = starts + seg_x2;
+            let ranges = deltas + seg_x2;
+            // Segments are sorted by end code, find the first one ending at or after c.
+            let (mut lo, mut hi) = (0, seg_x2 / 2);
+            while lo < hi {
+                let mid = (lo + hi) / 2;
+                if (u16_at(data, ends + mid * 2)? as u32) < c {
+                    lo = mid + 1;
+                } else {
+                    hi = mid;
+                }
+            }
+            if lo == seg_x2 / 2 {
+                return Ok(0);
+            }
+            let start = u16_at(data, starts + lo * 2)? as u32;
+            if c < start {
+                return Ok(0);
+            }
+            let delta = u16_at(data, deltas + lo * 2)?;
+            let range = u16_at(data, ranges + lo * 2)? as usize;
+            if range == 0 {
+                return Ok((c as u16).wrapping_add(delta));
+            }
+            let glyph = u16_at(data, ranges + lo * 2 + range + (c - start) as usize * 2)?;
+            Ok(if glyph == 0 { 0 } else { glyph.wrapping_add(delta) })
+        };
+        lookup().ok().filter(|&g| g < self.num_glyphs).unwrap_or(0)
+    }
+
+    fn advance(&self, glyph: u16) -> Result<u16, GsnError> {
+        let metric = glyph.min(self.h_metrics.saturating_sub(1)) as usize;
+        u16_at(&self.data, self.hmtx + metric * 4)
+    }
+
+    fn glyph_range(&self, glyph: u16) -> Result<(usize, usize), GsnError> {
+        let i = glyph as usize;
+        let (start, end) = if self.long_loca {
+            (u32_at(&self.data, self.loca + i * 4)? as usize, u32_at(&self.data, self.loca + i * 4 + 4)? as usize)
+        } else {
+            (u16_at(&self.data, self.loca + i * 2)? as usize * 2, u16_at(&self.data, self.loca + i * 2 + 2)? as usize * 2)
+        };
+        Ok((self.glyf + start, self.glyf + end))
+    }
+
+    fn outline(&self, glyph: u16, depth: u32, components: &mut u32) -> Result<Vec<Contour>, GsnError> {
+        let (start, end) = self.glyph_range(glyph)?;
+        if end <= start {
+            return Ok(vec![]);
+        }
+        let data = self.data.get(start..end).ok_or_else(|| ttf_error("glyph outside the glyf table"))?;
+        let contours = i16_at(data, 0)?;
+        if contours >= 0 {
+            simple_outline(data, contours as usize)
+        } else if depth < MAX_COMPONENT_DEPTH {
+            self.composite_outline(data, depth, components)
+        } else {
+            Err(ttf_error("composite glyphs nested too deep"))
+        }
+    }
+
+    // Components are other glyphs run through a 2x2 transform and an offset. Point matching isn't supported,
+    // those components are placed without an offset.
+    fn composite_outline(&self, data: &[u8], depth: u32, components: &mut u32) -> Result<Vec<Contour>, GsnError> {
+        let mut contours = vec![];
+        let mut pos = 10;
+        loop {
+            *components += 1;
+            if *components > MAX_COMPONENTS {
+                return Err(ttf_error("too many composite components"));
+            }
+            let flags = u16_at(data, pos)?;
+            let glyph = u16_at(data, pos + 2)?;
+            pos += 4;
+            let (dx, dy) = if flags & ARG_1_AND_2_ARE_WORDS != 0 {
+                pos += 4;
+                (i16_at(data, pos - 4)? as f32, i16_at(data, pos - 2)? as f32)
+            } else {
+                pos += 2;
+                let [a, b] = bytes_at::<2>(data, pos - 2)?;
+                (a as i8 as f32, b as i8 as f32)
+            };
+            let (dx, dy) = if flags & ARGS_ARE_XY_VALUES != 0 { (dx, dy) } else { (0.0, 0.0) };
+
+            let (mut a, mut b, mut c, mut d) = (1.0, 0.0, 0.0, 1.0);
+            if flags & WE_HAVE_A_SCALE != 0 {
+                a = f2dot14_at(data, pos)?;
+                d = a;
+                pos += 2;
+            } else if flags & WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+                a = f2dot14_at(data, pos)?;
+                d = f2dot14_at(data, pos + 2)?;
+                pos += 4;
+            } else if flags & WE_HAVE_A_TWO_BY_TWO != 0 {
+                a = f2dot14_at(data, pos)?;
+                b = f2dot14_at(data, pos + 2)?;
+                c = f2dot14_at(data, pos + 4)?;
+                d = f2dot14_at(data, pos + 6)?;
+                pos += 8;
+            }
+
+            for contour in self.outline(glyph, depth + 1, components)? {
+                contours.push(contour.into_iter().map(|(x, y, on)| (a * x + c * y + dx, b * x + d * y + dy, on)).collect());
+            }
+            if flags & MORE_COMPONENTS == 0 {
+                return Ok(contours);
+            }
+        }
+    }
+
+    fn rasterize(&self, c: char, size: u32) -> GsnCoverageGlyph {
+        let glyph = self.glyph_index(c);
+        let scale = self.scale(size);
+        let advance = self.advance(glyph).unwrap_or(0) as f32 * scale;
+        // A broken outline draws nothing rather than failing the whole string.
+        let contours = self.outline(glyph, 0, &mut 0).unwrap_or_default();
+
+        let points = contours.iter().flatten().map(|&(x, y, _)| (x * scale, -y * scale));
+        let (min_x, min_y, max_x, max_y) = points.fold((f32::MAX, f32::MAX, f32::MIN, f32::MIN), |(x0, y0, x1, y1), (x, y)| {
+            (x0.min(x), y0.min(y), x1.max(x), y1.max(y))
+        });
+        if min_x > max_x || !(max_x - min_x).is_finite() || !(max_y - min_y).is_finite() {
+            return GsnCoverageGlyph { advance, ..Default::default() };
+        }
+        let (left, top) = (min_x.floor(), min_y.floor());
+        let (width, height) = ((max_x.ceil() - left).max(1.0), (max_y.ceil() - top).max(1.0));
+        if width > MAX_RASTER_SIZE || height > MAX_RASTER_SIZE {
+            return GsnCoverageGlyph { advance, ..Default::default() };
+        }
+        let (width, height) = (width as usize, height as usize);
+
+        let mut raster = Raster::new(width, height);
+        for contour in &contours {
+            let points: Vec<_> = contour.iter().map(|&(x, y, on)| ((x * scale - left, -y * scale - top), on)).collect();
+            raster.contour(&points);
+        }
+
+        GsnCoverageGlyph {
+            width: width as u32,
+            height: height as u32,
+            x_offset: left as i32,
+            y_offset: (self.ascent * scale).round() as i32 + top as i32,
+            advance,
+            coverage: raster.coverage()
+        }
+    }
+}
+
+fn simple_outline(data: &[u8], contour_count: usize) -> Result<Vec<Contour>, GsnError> {
+    let mut ends = Vec::with_capacity(contour_count);
+    for i in 0..contour_count {
+        ends.push(u16_at(data, 10 + i * 2)? as usize);
+    }
+    let point_count = ends.last().map_or(0, |&e| e + 1);
+    let instructions = u16_at(data, 10 + contour_count * 2)? as usize;
+    let mut pos = 12 + contour_count * 2 + instructions;
+
+    let mut flags = Vec::with_capacity(point_count);
+    while flags.len() < point_count {
+        let flag = bytes_at::<1>(data, pos)?[0];
+        pos += 1;
+        let repeat = if flag & REPEAT != 0 {
+            pos += 1;
+            bytes_at::<1>(data, pos - 1)?[0] as usize
+        } else {
+            0
+        };
+        flags.extend(std::iter::repeat_n(flag, repeat + 1));
+    }
+    flags.truncate(point_count);
+
+    // Coordinates are deltas, either a byte with the sign in the flags or a signed word.
+    let mut coordinate = |short: u8, same_or_positive: u8| -> Result<Vec<f32>, GsnError> {
+        let mut value = 0i32;
+        let mut values = Vec::with_capacity(point_count);
+        for &flag in &flags {
+            if flag & short != 0 {
+                let delta = bytes_at::<1>(data, pos)?[0] as i32;
+                pos += 1;
+                value = value.wrapping_add(if flag & same_or_positive != 0 { delta } else { -delta });
+            } else if flag & same_or_positive == 0 {
+                value = value.wrapping_add(i16_at(data, pos)? as i32);
+                pos += 2;
+            }
+            values.push(value as f32);
+        }
+        Ok(values)
+    };
+    let xs = coordinate(X_SHORT, X_SAME_OR_POSITIVE)?;
+    let ys = coordinate(Y_SHORT, Y_SAME_OR_POSITIVE)?;
+
+    let mut contours = Vec::with_capacity(contour_count);
+    let mut start = 0;
+    for end in ends {
+        if end < start || end >= point_count {
+            return Err(ttf_error("contour end points out of order"));
+        }
+        contours.push((start..=end).map(|i| (xs[i], ys[i], flags[i] & ON_CURVE != 0)).collect());
+        start = end + 1;
+    }
+    Ok(contours)
+}
+
+type Point = (f32, f32);
+
+fn midpoint(a: Point, b: Point) -> Point {
+    ((a.0 + b.0) * 0.5, (a.1 + b.1) * 0.5)
+}
+
+// Signed area accumulation: every edge adds the area it covers to the right of itself, a running sum along
+// the rows then gives the exact coverage of each pixel under the non-zero rule.
+struct Raster {
+    width: usize,
+    height: usize,
+    area: Vec<f32>
+}
+
+impl Raster {
+    fn new(width: usize, height: usize) -> Raster {
+        // Edges on the right border spill up to two cells past a row, the last row needs the slack.
+        Raster { width, height, area: vec![0.0; width * height + 2] }
+    }
+
+    // TrueType contours are quadratic splines where two off curve points in a row imply an on curve one between them.
+    fn contour(&mut self, points: &[(Point, bool)]) {
+        let Some(&(first, first_on)) = points.first() else { return };
+        let (last, last_on) = points[points.len() - 1];
+        let (start, rest) = if first_on {
+            (first, &points[1..])
+        } else if last_on {
+            (last, &points[..points.len() - 1])
+        } else {
+            (midpoint(first, last), points)
+        };
+
+        let mut current = start;
+        let mut control: Option<Point> = None;
+        for &(p, on) in rest {
+            match (control, on) {
+                (Some(c), true) => {
+                    self.quad(current, c, p);
+                    current = p;
+                    control = None;
+                }
+                (None, true) => {
+                    self.line(current, p);
+                    current = p;
+                }
+                (Some(c), false) => {
+                    let m = midpoint(c, p);
+                    self.quad(current, c, m);
+                    current = m;
+                    control = Some(p);
+                }
+                (None, false) => control = Some(p)
+            }
+        }
+        match control {
+            Some(c) => self.quad(current, c, start),
+            None => self.line(current, start)
+        }
+    }
+
+    // Flattened into enough segments to stay well under a pixel off the true curve.
+    fn quad(&mut self, p0: Point, p1: Point, p2: Point) {
+        let dev = (p0.0 - 2.0 * p1.0 + p2.0, p0.1 - 2.0 * p1.1 + p2.1);
+        let dev_sq = dev.0 * dev.0 + dev.1 * dev.1;
+        if dev_sq < 0.333 {
+            self.line(p0, p2);
+            return;
+        }
+        let steps = 1 + (3.0 * dev_sq).sqrt().sqrt().floor() as usize;
+        let mut previous = p0;
+        for i in 1..steps {
+            let t = i as f32 / steps as f32;
+            let a = (p0.0 + (p1.0 - p0.0) * t, p0.1 + (p1.1 - p0.1) * t);
+            let b = (p1.0 + (p2.0 - p1.0) * t, p1.1 + (p2.1 - p1.1) * t);
+            let next = (a.0 + (b.0 - a.0) * t, a.1 + (b.1 - a.1) * t);
+            self.line(previous, next);
+            previous = next;
+        }
+        self.line(previous, p2);
+    }
+
+    fn line(&mut self, p0: Point, p1: Point) {
+        if (p0.1 - p1.1).abs() <= f32::EPSILON {
+            return;
+        }
+        let (direction, p0, p1) = if p0.1 < p1.1 { (1.0, p0, p1) } else { (-1.0, p1, p0) };
+        let dxdy = (p1.0 - p0.0) / (p1.1 - p0.1);
+        let mut x = p0.0.max(0.0);
+        let y_end = (p1.1.ceil() as usize).min(self.height);
+        for y in p0.1.max(0.0) as usize..y_end {
+            let row = y * self.width;
+            let dy = ((y + 1) as f32).min(p1.1) - (y as f32).max(p0.1);
+            let x_next = (x + dxdy * dy).clamp(0.0, self.width as f32);
+            let d = dy * direction;
+            let (x0, x1) = if x < x_next { (x, x_next) } else { (x_next, x) };
+            let x0_floor = x0.floor();
+            let x0i = x0_floor as usize;
+            let x1_ceil = x1.ceil();
+            let x1i = x1_ceil as usize;
+            if x1i <= x0i + 1 {
+                // Within one pixel, split the area by where the edge crosses it on average.
+                let xm = 0.5 * (x + x_next) - x0_floor;
+                self.area[row + x0i] += d - d * xm;
+                self.area[row + x0i + 1] += d * xm;
+            } else {
+                let s = (x1 - x0).recip();
+                let x0f = x0 - x0_floor;
+                let a0 = 0.5 * s * (1.0 - x0f) * (1.0 - x0f);
+                let x1f = x1 - x1_ceil + 1.0;
+                let am = 0.5 * s * x1f * x1f;
+                self.area[row + x0i] += d * a0;
+                if x1i == x0i + 2 {
+                    self.area[row + x0i + 1] += d * (1.0 - a0 - am);
+                } else {
+                    let a1 = s * (1.5 - x0f);
+                    self.area[row + x0i + 1] += d * (a1 - a0);
+                    for xi in x0i + 2..x1i - 1 {
+                        self.area[row + xi] += d * s;
+                    }
+                    let a2 = a1 + (x1i - x0i - 3) as f32 * s;
+                    self.area[row + x1i - 1] += d * (1.0 - a2 - am);
+                }
+                self.area[row + x1i] += d * am;
+            }
+            x = x_next;
+        }
+    }
+
+    fn coverage(&self) -> Vec<u8> {
+        let mut sum = 0.0;
+        self.area[..self.width * self.height].iter().map(|a| {
+            sum += a;
+            (sum.abs().min(1.0) * 255.0 + 0.5) as u8
+        }).collect()
+    }
+}
+
+impl GsnSprite {
+    // Anti-aliased text, edge pixels are blended with the color's alpha scaled by coverage. Under the Normal
+    // blend mode that goes through Alpha for the duration of the call, any other mode is used as set.
+    pub fn draw_truetype(&mut self, font: &mut GsnTrueType, x: i32, y: i32, text: &str, color: Pixel, size: u32) {
+        let previous = self.blend_mode().clone();
+        if matches!(previous, GsnBlendMode::Normal) {
+            self.set_blend_mode(GsnBlendMode::Alpha);
+        }
+        font.layout(text, size, |glyph, pen_x, pen_y| {
+            let left = x as i64 + pen_x + glyph.x_offset as i64;
+            let top = y as i64 + pen_y + glyph.y_offset as i64;
+            for gy in 0..glyph.height as usize {
+                for gx in 0..glyph.width as usize {
+                    let coverage = glyph.coverage[gy * glyph.width as usize + gx] as u32;
+                    if coverage == 0 {
+                        continue;
+                    }
+                    let alpha = (color.a() as u32 * coverage + 127) / 255;
+                    self.plot(left + gx as i64, top + gy as i64, pixel_rgba(color.r(), color.g(), color.b(), alpha as u8));
+                }
+            }
+        });
+        self.set_blend_mode(previous);
+    }
+}
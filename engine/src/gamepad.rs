@@ -0,0 +1,135 @@
+use std::array;
+use std::fmt;
+use std::str::FromStr;
+use glfw::{Action, GamepadAxis, GamepadButton, Glfw, JoystickId};
+use crate::error::GsnError;
+
+pub const GAMEPAD_BUTTONS: usize = 15;
+pub const GAMEPAD_AXES: usize = 6;
+
+// Standard gamepad layout, Xbox names. Declaration order matches glfw's button indices.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GsnGamepadButton {
+    A, B, X, Y,
+    LeftBumper, RightBumper,
+    Back, Start, Guide,
+    LeftThumb, RightThumb,
+    DpadUp, DpadRight, DpadDown, DpadLeft
+}
+
+impl GsnGamepadButton {
+    pub const ALL: [GsnGamepadButton; GAMEPAD_BUTTONS] = [
+        GsnGamepadButton::A, GsnGamepadButton::B, GsnGamepadButton::X, GsnGamepadButton::Y,
+        GsnGamepadButton::LeftBumper, GsnGamepadButton::RightBumper,
+        GsnGamepadButton::Back, GsnGamepadButton::Start, GsnGamepadButton::Guide,
+        GsnGamepadButton::LeftThumb, GsnGamepadButton::RightThumb,
+        GsnGamepadButton::DpadUp, GsnGamepadButton::DpadRight, GsnGamepadButton::DpadDown, GsnGamepadButton::DpadLeft
+    ];
+}
+
+impl fmt::Display for GsnGamepadButton {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl FromStr for GsnGamepadButton {
+    type Err = GsnError;
+
+    fn from_str(s: &str) -> Result<GsnGamepadButton, GsnError> {
+        GsnGamepadButton::ALL.iter().copied().find(|b| b.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| GsnError::InputMap(format!("unknown gamepad button {}", s)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GsnGamepadAxis {
+    LeftX, LeftY, RightX, RightY, LeftTrigger, RightTrigger
+}
+
+// One pad as a source reports it: axes from -1 to 1 with y pointing down, triggers resting at -1.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GsnGamepadState {
+    pub id: usize,
+    pub name: String,
+    pub buttons: [bool; GAMEPAD_BUTTONS],
+    pub axes: [f32; GAMEPAD_AXES]
+}
+
+impl GsnGamepadState {
+    pub fn new(id: usize, name: &str) -> GsnGamepadState {
+        let mut axes = [0.0; GAMEPAD_AXES];
+        axes[GsnGamepadAxis::LeftTrigger as usize] = -1.0;
+        axes[GsnGamepadAxis::RightTrigger as usize] = -1.0;
+        GsnGamepadState { id, name: name.to_string(), buttons: [false; GAMEPAD_BUTTONS], axes }
+    }
+}
+
+// Where the engine reads pads from every update. Swap in your own to drive input from a test or a replay.
+pub trait GsnGamepadSource {
+    // Every connected pad, pads missing from the list count as disconnected.
+    fn poll(&mut self) -> Vec<GsnGamepadState>;
+}
+
+// Joysticks glfw has a gamepad mapping for, anything else is left out.
+pub(crate) struct GlfwGamepads {
+    pub(crate) glfw: Glfw
+}
+
+impl GsnGamepadSource for GlfwGamepads {
+    fn poll(&mut self) -> Vec<GsnGamepadState> {
+        (0..16).filter_map(JoystickId::from_i32).filter_map(|id| {
+            let joystick = self.glfw.get_joystick(id);
+            if !joystick.is_gamepad() {
+                return None;
+            }
+            let state = joystick.get_gamepad_state()?;
+            Some(GsnGamepadState {
+                id: id as usize,
+                name: joystick.get_gamepad_name().unwrap_or_default(),
+                buttons: array::from_fn(|b| GamepadButton::from_i32(b as i32).is_some_and(|b| state.get_button_state(b) == Action::Press)),
+                axes: array::from_fn(|a| GamepadAxis::from_i32(a as i32).map_or(0.0, |a| state.get_axis(a)))
+            })
+        }).collect()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GsnGamepad {
+    pub(crate) state: GsnGamepadState,
+    pub(crate) deadzone: f32
+}
+
+impl GsnGamepad {
+    pub fn id(&self) -> usize {
+        self.state.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.state.name
+    }
+
+    pub fn button(&self, button: GsnGamepadButton) -> bool {
+        self.state.buttons[button as usize]
+    }
+
+    pub fn raw_axis(&self, axis: GsnGamepadAxis) -> f32 {
+        self.state.axes[axis as usize]
+    }
+
+    // Sticks get a radial deadzone, rescaled so a full push still reads 1. Triggers come back from 0 to 1.
+    pub fn axis(&self, axis: GsnGamepadAxis) -> f32 {
+        let dz = self.deadzone.clamp(0.0, 0.99);
+        let rescale = |v: f32| if v <= dz { 0.0 } else { (v.min(1.0) - dz) / (1.0 - dz) };
+        let stick = |x: GsnGamepadAxis, y: GsnGamepadAxis| {
+            let (x, y) = (self.raw_axis(x), self.raw_axis(y));
+            let length = (x * x + y * y).sqrt();
+            if length == 0.0 { 0.0 } else { rescale(length) / length }
+        };
+        match axis {
+            GsnGamepadAxis::LeftX | GsnGamepadAxis::LeftY => self.raw_axis(axis) * stick(GsnGamepadAxis::LeftX, GsnGamepadAxis::LeftY),
+            GsnGamepadAxis::RightX | GsnGamepadAxis::RightY => self.raw_axis(axis) * stick(GsnGamepadAxis::RightX, GsnGamepadAxis::RightY),
+            GsnGamepadAxis::LeftTrigger | GsnGamepadAxis::RightTrigger => rescale((self.raw_axis(axis) + 1.0) * 0.5)
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use crate::error::GsnError;
+use crate::renderer::{plan_upload, GsnBackend, GsnUploadStats};
+use crate::sprite::GsnSprite;
+
+// CPU only backend, nothing here touches a window or a GL context.
+// The buffer is the frame, render() only counts what a GPU backend would upload.
+pub struct GsnHeadless {
+    buffer: GsnSprite,
+    upload_stats: GsnUploadStats,
+}
+
+pub fn new_gsn_headless() -> GsnHeadless {
+    new_gsn_headless_sized(0, 0)
+}
+
+// Already initialized, so the engine doesn't have to handle an error that can't happen.
+pub(crate) fn new_gsn_headless_sized(width: u32, height: u32) -> GsnHeadless {
+    GsnHeadless {
+        buffer: GsnSprite::new(width, height),
+        upload_stats: GsnUploadStats::default(),
+    }
+}
+
+impl GsnBackend for GsnHeadless {
+    // Never fails.
+    fn initialize(&mut self, width: u32, height: u32) -> Result<(), GsnError> {
+        self.buffer = GsnSprite::new(width, height);
+        Ok(())
+    }
+
+    fn render(&mut self) {
+        plan_upload(&mut self.buffer, &mut self.upload_stats);
+    }
+
+    fn buffer(&self) -> &GsnSprite {
+        &self.buffer
+    }
+
+    fn buffer_mut(&mut self) -> &mut GsnSprite {
+        &mut self.buffer
+    }
+
+    fn upload_stats(&self) -> GsnUploadStats {
+        self.upload_stats
+    }
+}
@@ -0,0 +1,171 @@
+use std::fs;
+use std::path::Path;
+use crate::error::GsnError;
+use crate::image::{check_size, encode_error, ByteReader};
+use crate::sprite::{pixel_rgba, GsnSprite, Pixel};
+
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+const BI_ALPHABITFIELDS: u32 = 6;
+const FILE_HEADER_SIZE: usize = 14;
+const V4_HEADER_SIZE: usize = 108;
+
+impl GsnSprite {
+    pub fn load_bmp<P: AsRef<Path>>(path: P) -> Result<GsnSprite, GsnError> {
+        GsnSprite::from_bmp_bytes(&fs::read(path)?)
+    }
+
+    // Uncompressed 1, 2, 4, 8, 16, 24 and 32 bit BMPs, with or without bitfield masks, bottom up or top down.
+    pub fn from_bmp_bytes(bytes: &[u8]) -> Result<GsnSprite, GsnError> {
+        let mut r = ByteReader::new("bmp", bytes);
+        if r.take(2)? != b"BM" {
+            return Err(r.error("missing BM signature"));
+        }
+        r.take(8)?;
+        let data_offset = r.u32_le()? as usize;
+
+        let header_size = r.u32_le()? as usize;
+        let (width, height, bpp, compression, colors_used) = if header_size == 12 {
+            let width = r.u16_le()? as i32;
+            let height = r.u16_le()? as i32;
+            r.u16_le()?;
+            let bpp = r.u16_le()?;
+            (width, height, bpp, BI_RGB, 0)
+        } else if header_size >= 40 {
+            let width = r.i32_le()?;
+            let height = r.i32_le()?;
+            r.u16_le()?;
+            let bpp = r.u16_le()?;
+            let compression = r.u32_le()?;
+            r.take(12)?;
+            let colors_used = r.u32_le()?;
+            r.u32_le()?;
+            (width, height, bpp, compression, colors_used)
+        } else {
+            return Err(r.error(&format!("unsupported header size {}", header_size)));
+        };
+
+        // Masks live at the end of V2+ headers, or right after a plain 40 byte header.
+        let mut masks = match bpp {
+            16 => [0x7c00, 0x03e0, 0x001f, 0],
+            32 => [0x00ff_0000, 0x0000_ff00, 0x0000_00ff, 0],
+            _ => [0; 4]
+        };
+        let mut palette_offset = FILE_HEADER_SIZE + header_size;
+        match compression {
+            BI_RGB => {}
+            BI_BITFIELDS | BI_ALPHABITFIELDS if bpp == 16 || bpp == 32 => {
+                let mask_count = if header_size >= 56 || compression == BI_ALPHABITFIELDS { 4 } else { 3 };
+                r.seek(FILE_HEADER_SIZE + 40)?;
+                masks = [0; 4];
+                for mask in masks.iter_mut().take(mask_count) {
+                    *mask = r.u32_le()?;
+                }
+                if header_size == 40 {
+                    palette_offset += mask_count * 4;
+                }
+            }
+            _ => return Err(r.error(&format!("unsupported compression {}", compression)))
+        }
+        r.seek(palette_offset)?;
+
+        let palette: Vec<Pixel> = if bpp <= 8 {
+            let count = if colors_used == 0 { 1 << bpp } else { colors_used.min(256) as usize };
+            let entry_size = if header_size == 12 { 3 } else { 4 };
+            let mut palette = Vec::with_capacity(count);
+            for _ in 0..count {
+                let c = r.take(entry_size)?;
+                palette.push(pixel_rgba(c[2], c[1], c[0], 255));
+            }
+            palette
+        } else {
+            vec![]
+        };
+
+        let top_down = height < 0;
+        let (width, height) = check_size("bmp", width.unsigned_abs() as u64, height.unsigned_abs() as u64)?;
+        let stride = (width as usize * bpp as usize).div_ceil(32) * 4;
+        r.seek(data_offset)?;
+        let rows = r.take(stride * height as usize)?;
+
+        // 32 bit BI_RGB files are supposed to ignore the 4th byte, but plenty of tools store alpha in it.
+        if bpp == 32 && compression == BI_RGB && rows.chunks_exact(4).any(|c| c[3] != 0) {
+            masks[3] = 0xff00_0000;
+        }
+
+        let mut sprite = GsnSprite::new(width, height);
+        for row in 0..height {
+            let y = if top_down { row } else { height - 1 - row };
+            let line = &rows[row as usize * stride..(row as usize + 1) * stride];
+            for x in 0..width as usize {
+                let p = match bpp {
+                    1 | 2 | 4 | 8 => {
+                        let bit = x * bpp as usize;
+                        let index = (line[bit / 8] >> (8 - bpp as usize - bit % 8)) & ((1u16 << bpp) - 1) as u8;
+                        *palette.get(index as usize).ok_or_else(|| r.error("palette index out of range"))?
+                    }
+                    16 => from_masks(u16::from_le_bytes([line[x * 2], line[x * 2 + 1]]) as u32, &masks),
+                    24 => pixel_rgba(line[x * 3 + 2], line[x * 3 + 1], line[x * 3], 255),
+                    32 => from_masks(u32::from_le_bytes([line[x * 4], line[x * 4 + 1], line[x * 4 + 2], line[x * 4 + 3]]), &masks),
+                    _ => return Err(r.error(&format!("unsupported bit depth {}", bpp)))
+                };
+                sprite.data[y as usize * width as usize + x] = p;
+            }
+        }
+        Ok(sprite)
+    }
+
+    pub fn save_bmp<P: AsRef<Path>>(&self, path: P) -> Result<(), GsnError> {
+        fs::write(path, self.to_bmp_bytes()?)?;
+        Ok(())
+    }
+
+    // 32 bit top down BGRA with a V4 header, so alpha survives the round trip.
+    // Sizes are signed 32 bit and the file size unsigned 32 bit, sprites past either are an encode error.
+    pub fn to_bmp_bytes(&self) -> Result<Vec<u8>, GsnError> {
+        let data_offset = FILE_HEADER_SIZE + V4_HEADER_SIZE;
+        let image_size = self.data.len() * 4;
+        let (Ok(width), Ok(height), Ok(file_size)) = (i32::try_from(self.width), i32::try_from(self.height), u32::try_from(data_offset + image_size)) else {
+            return Err(encode_error("bmp", &format!("{}x{} is too large", self.width, self.height)));
+        };
+        let mut out = Vec::with_capacity(data_offset + image_size);
+
+        out.extend_from_slice(b"BM");
+        out.extend_from_slice(&file_size.to_le_bytes());
+        out.extend_from_slice(&[0; 4]);
+        out.extend_from_slice(&(data_offset as u32).to_le_bytes());
+
+        out.extend_from_slice(&(V4_HEADER_SIZE as u32).to_le_bytes());
+        out.extend_from_slice(&width.to_le_bytes());
+        out.extend_from_slice(&(-height).to_le_bytes());
+        out.extend_from_slice(&1u16.to_le_bytes());
+        out.extend_from_slice(&32u16.to_le_bytes());
+        out.extend_from_slice(&BI_BITFIELDS.to_le_bytes());
+        out.extend_from_slice(&(image_size as u32).to_le_bytes());
+        out.extend_from_slice(&2835u32.to_le_bytes());
+        out.extend_from_slice(&2835u32.to_le_bytes());
+        out.extend_from_slice(&[0; 8]);
+        for mask in [0x00ff_0000u32, 0x0000_ff00, 0x0000_00ff, 0xff00_0000] {
+            out.extend_from_slice(&mask.to_le_bytes());
+        }
+        out.extend_from_slice(b"BGRs");
+        out.resize(data_offset, 0);
+
+        for p in &self.data {
+            out.extend_from_slice(&[p.b(), p.g(), p.r(), p.a()]);
+        }
+        Ok(out)
+    }
+}
+
+fn from_masks(value: u32, masks: &[u32; 4]) -> Pixel {
+    let channel = |mask: u32, default: u8| -> u8 {
+        if mask == 0 {
+            return default;
+        }
+        let max = (1u64 << mask.count_ones()) - 1;
+        let v = ((value & mask) >> mask.trailing_zeros()) as u64;
+        ((v * 255 + max / 2) / max) as u8
+    };
+    pixel_rgba(channel(masks[0], 0), channel(masks[1], 0), channel(masks[2], 0), channel(masks[3], 255))
+}
@@ -0,0 +1,117 @@
+use std::fs;
+use std::path::Path;
+use crate::error::GsnError;
+use crate::image::check_size;
+use crate::sprite::GsnSprite;
+
+const ORIENTATION_TAG: u16 = 0x0112;
+
+impl GsnSprite {
+    pub fn load_jpeg<P: AsRef<Path>>(path: P) -> Result<GsnSprite, GsnError> {
+        GsnSprite::from_jpeg_bytes(&fs::read(path)?)
+    }
+
+    // Baseline and progressive JPEG. The EXIF orientation is applied, so the sprite comes out the way a photo viewer shows it.
+    pub fn from_jpeg_bytes(bytes: &[u8]) -> Result<GsnSprite, GsnError> {
+        let mut decoder = jpeg_decoder::Decoder::new(bytes);
+        decoder.read_info().map_err(|e| GsnError::ImageDecode(format!("jpeg: {}", e)))?;
+        let info = decoder.info().ok_or_else(|| GsnError::ImageDecode("jpeg: missing frame header".to_string()))?;
+        check_size("jpeg", info.width as u64, info.height as u64)?;
+        let data = decoder.decode().map_err(|e| GsnError::ImageDecode(format!("jpeg: {}", e)))?;
+
+        let rgba: Vec<u8> = match info.pixel_format {
+            jpeg_decoder::PixelFormat::RGB24 => data.chunks_exact(3).flat_map(|c| [c[0], c[1], c[2], 255]).collect(),
+            jpeg_decoder::PixelFormat::L8 => data.iter().flat_map(|&l| [l, l, l, 255]).collect(),
+            jpeg_decoder::PixelFormat::L16 => {
+                let max = (1u32 << frame_precision(bytes).unwrap_or(16).clamp(9, 16)) - 1;
+                data.chunks_exact(2).flat_map(|c| {
+                    let v = (u16::from_ne_bytes([c[0], c[1]]) as u32).min(max);
+                    let l = ((v * 255 + max / 2) / max) as u8;
+                    [l, l, l, 255]
+                }).collect()
+            },
+            jpeg_decoder::PixelFormat::CMYK32 => data.chunks_exact(4).flat_map(|c| {
+                let k = 255 - c[3] as u32;
+                let channel = |v: u8| ((255 - v as u32) * k / 255) as u8;
+                [channel(c[0]), channel(c[1]), channel(c[2]), 255]
+            }).collect()
+        };
+        let sprite = GsnSprite::from_rgba8(info.width as u32, info.height as u32, &rgba)?;
+
+        let orientation = decoder.exif_data().and_then(exif_orientation).unwrap_or(1);
+        Ok(orient(sprite, orientation))
+    }
+}
+
+// Sample precision out of the SOFn header, jpeg_decoder hands out L16 for 9 to 16 bit frames without saying which.
+fn frame_precision(bytes: &[u8]) -> Option<u8> {
+    let mut i = 2;
+    loop {
+        if *bytes.get(i)? != 0xFF {
+            return None;
+        }
+        let marker = *bytes.get(i + 1)?;
+        if marker == 0xFF {
+            i += 1;
+            continue;
+        }
+        if (0xC0..=0xCF).contains(&marker) && ![0xC4, 0xC8, 0xCC].contains(&marker) {
+            return bytes.get(i + 4).copied();
+        }
+        i += 2 + u16::from_be_bytes([*bytes.get(i + 2)?, *bytes.get(i + 3)?]) as usize;
+    }
+}
+
+// Reads tag 0x0112 out of IFD0, exif starts at the TIFF header.
+fn exif_orientation(exif: &[u8]) -> Option<u16> {
+    let big_endian = match exif.get(0..4)? {
+        [b'I', b'I', 42, 0] => false,
+        [b'M', b'M', 0, 42] => true,
+        _ => return None
+    };
+    let u16_at = |i: usize| -> Option<u16> {
+        let b = [*exif.get(i)?, *exif.get(i + 1)?];
+        Some(if big_endian { u16::from_be_bytes(b) } else { u16::from_le_bytes(b) })
+    };
+    let u32_at = |i: usize| -> Option<u32> {
+        let b = [*exif.get(i)?, *exif.get(i + 1)?, *exif.get(i + 2)?, *exif.get(i + 3)?];
+        Some(if big_endian { u32::from_be_bytes(b) } else { u32::from_le_bytes(b) })
+    };
+
+    let ifd = u32_at(4)? as usize;
+    let entries = u16_at(ifd)? as usize;
+    for i in 0..entries {
+        let entry = ifd + 2 + i * 12;
+        if u16_at(entry)? == ORIENTATION_TAG {
+            return u16_at(entry + 8).filter(|o| (1..=8).contains(o));
+        }
+    }
+    None
+}
+
+// Maps every source pixel to where EXIF orientation 1-8 says it belongs.
+fn orient(sprite: GsnSprite, orientation: u16) -> GsnSprite {
+    if orientation == 1 {
+        return sprite;
+    }
+    let (w, h) = (sprite.width, sprite.height);
+    let transposed = orientation >= 5;
+    let mut out = if transposed { GsnSprite::new(h, w) } else { GsnSprite::new(w, h) };
+    for y in 0..h {
+        for x in 0..w {
+            let (dx, dy) = match orientation {
+                2 => (w - 1 - x, y),
+                3 => (w - 1 - x, h - 1 - y),
+                4 => (x, h - 1 - y),
+                5 => (y, x),
+                6 => (h - 1 - y, x),
+                7 => (h - 1 - y, w - 1 - x),
+                _ => (y, w - 1 - x)
+            };
+            if let Some(p) = sprite.get_pixel(x, y) {
+                out.set_pixel(dx, dy, p);
+            }
+        }
+    }
+    out
+}
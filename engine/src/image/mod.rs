@@ -0,0 +1,88 @@
+#[cfg(feature = "png")]
+mod png;
+#[cfg(feature = "jpeg")]
+mod jpeg;
+mod bmp;
+mod tga;
+mod netpbm;
+mod qoi;
+
+use crate::error::GsnError;
+
+pub(crate) fn decode_error(format: &str, message: &str) -> GsnError {
+    GsnError::ImageDecode(format!("{}: {}", format, message))
+}
+
+pub(crate) fn encode_error(format: &str, message: &str) -> GsnError {
+    GsnError::ImageEncode(format!("{}: {}", format, message))
+}
+
+// Bounds checked cursor over an encoded file, running past the end is a decode error instead of a panic.
+pub(crate) struct ByteReader<'a> {
+    format: &'static str,
+    bytes: &'a [u8],
+    pub(crate) pos: usize
+}
+
+impl<'a> ByteReader<'a> {
+    pub(crate) fn new(format: &'static str, bytes: &'a [u8]) -> ByteReader<'a> {
+        ByteReader { format, bytes, pos: 0 }
+    }
+
+    pub(crate) fn error(&self, message: &str) -> GsnError {
+        decode_error(self.format, message)
+    }
+
+    pub(crate) fn take(&mut self, n: usize) -> Result<&'a [u8], GsnError> {
+        if self.bytes.len() - self.pos < n {
+            return Err(self.error("unexpected end of file"));
+        }
+        let slice = &self.bytes[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(slice)
+    }
+
+    pub(crate) fn seek(&mut self, pos: usize) -> Result<(), GsnError> {
+        if pos > self.bytes.len() {
+            return Err(self.error("offset past the end of file"));
+        }
+        self.pos = pos;
+        Ok(())
+    }
+
+    pub(crate) fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, GsnError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u16_le(&mut self) -> Result<u16, GsnError> {
+        let b = self.take(2)?;
+        Ok(u16::from_le_bytes([b[0], b[1]]))
+    }
+
+    pub(crate) fn u32_le(&mut self) -> Result<u32, GsnError> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    pub(crate) fn i32_le(&mut self) -> Result<i32, GsnError> {
+        Ok(self.u32_le()? as i32)
+    }
+
+    pub(crate) fn u32_be(&mut self) -> Result<u32, GsnError> {
+        let b = self.take(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+}
+
+// Guards against headers claiming absurd sizes before anything gets allocated.
+pub(crate) fn check_size(format: &str, width: u64, height: u64) -> Result<(u32, u32), GsnError> {
+    const MAX_PIXELS: u64 = 1 << 28;
+    if width == 0 || height == 0 || width.checked_mul(height).is_none_or(|n| n > MAX_PIXELS) {
+        return Err(decode_error(format, &format!("unsupported image size {}x{}", width, height)));
+    }
+    Ok((width as u32, height as u32))
+}
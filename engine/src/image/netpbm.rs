@@ -0,0 +1,121 @@
+use std::fs;
+use std::path::Path;
+use crate::error::GsnError;
+use crate::image::{check_size, decode_error, ByteReader};
+use crate::sprite::{pixel_rgba, GsnSprite};
+
+impl GsnSprite {
+    pub fn load_ppm<P: AsRef<Path>>(path: P) -> Result<GsnSprite, GsnError> {
+        GsnSprite::from_ppm_bytes(&fs::read(path)?)
+    }
+
+    // Reads PPM and PGM, both the ASCII (P3, P2) and binary (P6, P5) flavours, 8 or 16 bit samples.
+    pub fn from_ppm_bytes(bytes: &[u8]) -> Result<GsnSprite, GsnError> {
+        let mut r = ByteReader::new("ppm", bytes);
+        let magic = r.take(2)?;
+        let (channels, ascii) = match magic {
+            b"P2" => (1, true),
+            b"P3" => (3, true),
+            b"P5" => (1, false),
+            b"P6" => (3, false),
+            _ => return Err(r.error("unsupported magic number"))
+        };
+        let width = header_value(&mut r)?;
+        let height = header_value(&mut r)?;
+        let max = header_value(&mut r)?;
+        if max == 0 || max > u16::MAX as u64 {
+            return Err(r.error(&format!("invalid maximum value {}", max)));
+        }
+        let (width, height) = check_size("ppm", width, height)?;
+
+        // Every ASCII sample takes at least one byte, so the reservation is bounded by what's left of the file.
+        let count = width as usize * height as usize * channels;
+        let mut samples = Vec::with_capacity(if ascii { count.min(r.remaining()) } else { 0 });
+        if ascii {
+            for _ in 0..count {
+                samples.push(header_value(&mut r)?);
+            }
+        } else {
+            // Exactly one whitespace byte separates the header from the raster.
+            r.u8()?;
+            let wide = max > 255;
+            let raster = r.take(count * if wide { 2 } else { 1 })?;
+            samples.reserve_exact(count);
+            if wide {
+                samples.extend(raster.chunks_exact(2).map(|c| u16::from_be_bytes([c[0], c[1]]) as u64));
+            } else {
+                samples.extend(raster.iter().map(|&c| c as u64));
+            }
+        }
+
+        let scale = |v: u64| ((v.min(max) * 255 + max / 2) / max) as u8;
+        let mut sprite = GsnSprite::new(width, height);
+        for (p, s) in sprite.data.iter_mut().zip(samples.chunks_exact(channels)) {
+            *p = if channels == 1 {
+                let l = scale(s[0]);
+                pixel_rgba(l, l, l, 255)
+            } else {
+                pixel_rgba(scale(s[0]), scale(s[1]), scale(s[2]), 255)
+            };
+        }
+        Ok(sprite)
+    }
+
+    pub fn save_ppm<P: AsRef<Path>>(&self, path: P) -> Result<(), GsnError> {
+        fs::write(path, self.to_ppm_bytes())?;
+        Ok(())
+    }
+
+    pub fn save_pgm<P: AsRef<Path>>(&self, path: P) -> Result<(), GsnError> {
+        fs::write(path, self.to_pgm_bytes())?;
+        Ok(())
+    }
+
+    // Binary P6, alpha is dropped.
+    pub fn to_ppm_bytes(&self) -> Vec<u8> {
+        let mut out = format!("P6\n{} {}\n255\n", self.width, self.height).into_bytes();
+        for p in &self.data {
+            out.extend_from_slice(&[p.r(), p.g(), p.b()]);
+        }
+        out
+    }
+
+    // Binary P5 of the Rec. 601 luma, alpha is dropped.
+    pub fn to_pgm_bytes(&self) -> Vec<u8> {
+        let mut out = format!("P5\n{} {}\n255\n", self.width, self.height).into_bytes();
+        for p in &self.data {
+            let l = (299 * p.r() as u32 + 587 * p.g() as u32 + 114 * p.b() as u32 + 500) / 1000;
+            out.push(l as u8);
+        }
+        out
+    }
+}
+
+// Next decimal number, skipping whitespace and # comments.
+fn header_value(r: &mut ByteReader) -> Result<u64, GsnError> {
+    loop {
+        match r.take(1)?[0] {
+            b'#' => while r.remaining() > 0 && r.take(1)?[0] != b'\n' {},
+            c if c.is_ascii_whitespace() => {}
+            _ => {
+                r.pos -= 1;
+                break;
+            }
+        }
+    }
+    let mut value: u64 = 0;
+    let mut digits = 0;
+    while r.remaining() > 0 {
+        let c = r.take(1)?[0];
+        if !c.is_ascii_digit() {
+            r.pos -= 1;
+            break;
+        }
+        value = value.saturating_mul(10).saturating_add((c - b'0') as u64);
+        digits += 1;
+    }
+    if digits == 0 {
+        return Err(decode_error("ppm", "expected a number"));
+    }
+    Ok(value)
+}
@@ -0,0 +1,51 @@
+use std::fs;
+use std::path::Path;
+use crate::error::GsnError;
+use crate::image::check_size;
+use crate::sprite::GsnSprite;
+
+impl GsnSprite {
+    pub fn load_png<P: AsRef<Path>>(path: P) -> Result<GsnSprite, GsnError> {
+        GsnSprite::from_png_bytes(&fs::read(path)?)
+    }
+
+    // Every PNG flavour is accepted, palettes, grayscale and 16 bit channels are expanded to 8 bit RGBA.
+    pub fn from_png_bytes(bytes: &[u8]) -> Result<GsnSprite, GsnError> {
+        let decode_error = |e: png::DecodingError| GsnError::ImageDecode(format!("png: {}", e));
+
+        let mut decoder = png::Decoder::new(bytes);
+        decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+        let mut reader = decoder.read_info().map_err(decode_error)?;
+        check_size("png", reader.info().width as u64, reader.info().height as u64)?;
+        let mut buf = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut buf).map_err(decode_error)?;
+        let buf = &buf[..info.buffer_size()];
+
+        let rgba: Vec<u8> = match info.color_type {
+            png::ColorType::Rgba => buf.to_vec(),
+            png::ColorType::Rgb => buf.chunks_exact(3).flat_map(|c| [c[0], c[1], c[2], 255]).collect(),
+            png::ColorType::GrayscaleAlpha => buf.chunks_exact(2).flat_map(|c| [c[0], c[0], c[0], c[1]]).collect(),
+            png::ColorType::Grayscale => buf.iter().flat_map(|&c| [c, c, c, 255]).collect(),
+            png::ColorType::Indexed => return Err(GsnError::ImageDecode("png: palette was not expanded".to_string()))
+        };
+        GsnSprite::from_rgba8(info.width, info.height, &rgba)
+    }
+
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<(), GsnError> {
+        fs::write(path, self.to_png_bytes()?)?;
+        Ok(())
+    }
+
+    pub fn to_png_bytes(&self) -> Result<Vec<u8>, GsnError> {
+        let encode_error = |e: png::EncodingError| GsnError::ImageEncode(format!("png: {}", e));
+
+        let mut out = Vec::new();
+        let mut encoder = png::Encoder::new(&mut out, self.width, self.height);
+        encoder.set_color(png::ColorType::Rgba);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header().map_err(encode_error)?;
+        writer.write_image_data(&self.to_rgba8()).map_err(encode_error)?;
+        writer.finish().map_err(encode_error)?;
+        Ok(out)
+    }
+}
@@ -0,0 +1,137 @@
+use std::fs;
+use std::path::Path;
+use crate::error::GsnError;
+use crate::image::{check_size, ByteReader};
+use crate::sprite::GsnSprite;
+
+const OP_INDEX: u8 = 0x00;
+const OP_DIFF: u8 = 0x40;
+const OP_LUMA: u8 = 0x80;
+const OP_RUN: u8 = 0xc0;
+const OP_RGB: u8 = 0xfe;
+const OP_RGBA: u8 = 0xff;
+const MASK_2: u8 = 0xc0;
+const END_MARKER: [u8; 8] = [0, 0, 0, 0, 0, 0, 0, 1];
+
+fn hash(p: [u8; 4]) -> usize {
+    (p[0] as usize * 3 + p[1] as usize * 5 + p[2] as usize * 7 + p[3] as usize * 11) % 64
+}
+
+impl GsnSprite {
+    pub fn load_qoi<P: AsRef<Path>>(path: P) -> Result<GsnSprite, GsnError> {
+        GsnSprite::from_qoi_bytes(&fs::read(path)?)
+    }
+
+    pub fn from_qoi_bytes(bytes: &[u8]) -> Result<GsnSprite, GsnError> {
+        let mut r = ByteReader::new("qoi", bytes);
+        if r.take(4)? != b"qoif" {
+            return Err(r.error("missing qoif signature"));
+        }
+        let width = r.u32_be()?;
+        let height = r.u32_be()?;
+        let channels = r.u8()?;
+        r.u8()?;
+        if channels != 3 && channels != 4 {
+            return Err(r.error(&format!("invalid channel count {}", channels)));
+        }
+        let (width, height) = check_size("qoi", width as u64, height as u64)?;
+
+        // Runs make a few bytes expand to many pixels, so the output grows as it's decoded
+        // instead of trusting the header's size up front.
+        let count = width as usize * height as usize;
+        let mut rgba = Vec::new();
+        let mut index = [[0u8; 4]; 64];
+        let mut p = [0, 0, 0, 255];
+        let mut run = 0;
+        for _ in 0..count {
+            if run > 0 {
+                run -= 1;
+            } else {
+                let b1 = r.u8()?;
+                if b1 == OP_RGB {
+                    let c = r.take(3)?;
+                    p = [c[0], c[1], c[2], p[3]];
+                } else if b1 == OP_RGBA {
+                    let c = r.take(4)?;
+                    p = [c[0], c[1], c[2], c[3]];
+                } else if b1 & MASK_2 == OP_INDEX {
+                    p = index[b1 as usize];
+                } else if b1 & MASK_2 == OP_DIFF {
+                    p[0] = p[0].wrapping_add((b1 >> 4) & 0x03).wrapping_sub(2);
+                    p[1] = p[1].wrapping_add((b1 >> 2) & 0x03).wrapping_sub(2);
+                    p[2] = p[2].wrapping_add(b1 & 0x03).wrapping_sub(2);
+                } else if b1 & MASK_2 == OP_LUMA {
+                    let b2 = r.u8()?;
+                    let dg = (b1 & 0x3f).wrapping_sub(32);
+                    p[0] = p[0].wrapping_add(dg.wrapping_sub(8).wrapping_add(b2 >> 4));
+                    p[1] = p[1].wrapping_add(dg);
+                    p[2] = p[2].wrapping_add(dg.wrapping_sub(8).wrapping_add(b2 & 0x0f));
+                } else {
+                    run = b1 & 0x3f;
+                }
+                index[hash(p)] = p;
+            }
+            rgba.extend_from_slice(&p);
+        }
+        GsnSprite::from_rgba8(width, height, &rgba)
+    }
+
+    pub fn save_qoi<P: AsRef<Path>>(&self, path: P) -> Result<(), GsnError> {
+        fs::write(path, self.to_qoi_bytes())?;
+        Ok(())
+    }
+
+    pub fn to_qoi_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(14 + self.data.len() * 2 + END_MARKER.len());
+        out.extend_from_slice(b"qoif");
+        out.extend_from_slice(&self.width.to_be_bytes());
+        out.extend_from_slice(&self.height.to_be_bytes());
+        out.extend_from_slice(&[4, 0]);
+
+        let mut index = [[0u8; 4]; 64];
+        let mut prev = [0, 0, 0, 255];
+        let mut run = 0u8;
+        for (i, pixel) in self.data.iter().enumerate() {
+            let p = [pixel.r(), pixel.g(), pixel.b(), pixel.a()];
+            if p == prev {
+                run += 1;
+                if run == 62 || i == self.data.len() - 1 {
+                    out.push(OP_RUN | (run - 1));
+                    run = 0;
+                }
+                continue;
+            }
+            if run > 0 {
+                out.push(OP_RUN | (run - 1));
+                run = 0;
+            }
+
+            let h = hash(p);
+            if index[h] == p {
+                out.push(OP_INDEX | h as u8);
+            } else {
+                index[h] = p;
+                if p[3] == prev[3] {
+                    let dr = p[0].wrapping_sub(prev[0]) as i8;
+                    let dg = p[1].wrapping_sub(prev[1]) as i8;
+                    let db = p[2].wrapping_sub(prev[2]) as i8;
+                    let dr_dg = dr.wrapping_sub(dg);
+                    let db_dg = db.wrapping_sub(dg);
+                    if (-2..2).contains(&dr) && (-2..2).contains(&dg) && (-2..2).contains(&db) {
+                        out.push(OP_DIFF | ((dr + 2) as u8) << 4 | ((dg + 2) as u8) << 2 | (db + 2) as u8);
+                    } else if (-8..8).contains(&dr_dg) && (-32..32).contains(&dg) && (-8..8).contains(&db_dg) {
+                        out.push(OP_LUMA | (dg + 32) as u8);
+                        out.push(((dr_dg + 8) as u8) << 4 | (db_dg + 8) as u8);
+                    } else {
+                        out.extend_from_slice(&[OP_RGB, p[0], p[1], p[2]]);
+                    }
+                } else {
+                    out.extend_from_slice(&[OP_RGBA, p[0], p[1], p[2], p[3]]);
+                }
+            }
+            prev = p;
+        }
+        out.extend_from_slice(&END_MARKER);
+        out
+    }
+}
@@ -0,0 +1,162 @@
+use std::fs;
+use std::path::Path;
+use crate::error::GsnError;
+use crate::image::{check_size, encode_error, ByteReader};
+use crate::sprite::{pixel_rgba, GsnSprite, Pixel};
+
+const COLOR_MAPPED: u8 = 1;
+const TRUE_COLOR: u8 = 2;
+const GRAYSCALE: u8 = 3;
+const RLE: u8 = 8;
+const RIGHT_TO_LEFT: u8 = 0x10;
+const TOP_TO_BOTTOM: u8 = 0x20;
+
+impl GsnSprite {
+    pub fn load_tga<P: AsRef<Path>>(path: P) -> Result<GsnSprite, GsnError> {
+        GsnSprite::from_tga_bytes(&fs::read(path)?)
+    }
+
+    // Color mapped, true color and grayscale images, raw or RLE, in any of the four origins.
+    pub fn from_tga_bytes(bytes: &[u8]) -> Result<GsnSprite, GsnError> {
+        let mut r = ByteReader::new("tga", bytes);
+        let id_length = r.u8()?;
+        let color_map_type = r.u8()?;
+        let image_type = r.u8()?;
+        let map_first = r.u16_le()? as usize;
+        let map_length = r.u16_le()? as usize;
+        let map_entry_bits = r.u8()?;
+        r.take(4)?;
+        let width = r.u16_le()?;
+        let height = r.u16_le()?;
+        let bpp = r.u8()?;
+        let descriptor = r.u8()?;
+        r.take(id_length as usize)?;
+
+        let alpha_bits = descriptor & 0x0f;
+        let base_type = image_type & !RLE;
+        if !matches!(base_type, COLOR_MAPPED | TRUE_COLOR | GRAYSCALE) {
+            return Err(r.error(&format!("unsupported image type {}", image_type)));
+        }
+
+        let mut color_map = Vec::new();
+        if color_map_type == 1 {
+            let entry_bytes = (map_entry_bits as usize).div_ceil(8);
+            for _ in 0..map_length {
+                let entry = r.take(entry_bytes)?;
+                color_map.push(true_color(entry, map_entry_bits, alpha_bits).ok_or_else(|| r.error("unsupported color map entry size"))?);
+            }
+        }
+
+        let (width, height) = check_size("tga", width as u64, height as u64)?;
+        let pixel_bytes = (bpp as usize).div_ceil(8);
+        if pixel_bytes == 0 || pixel_bytes > 4 {
+            return Err(r.error(&format!("unsupported pixel depth {}", bpp)));
+        }
+        let decode = |raw: &[u8]| -> Option<Pixel> {
+            match base_type {
+                COLOR_MAPPED => {
+                    let index = if pixel_bytes == 1 { raw[0] as usize } else { u16::from_le_bytes([raw[0], raw[1]]) as usize };
+                    index.checked_sub(map_first).and_then(|i| color_map.get(i)).copied()
+                }
+                GRAYSCALE => {
+                    let a = if pixel_bytes == 2 && alpha_bits > 0 { raw[1] } else { 255 };
+                    Some(pixel_rgba(raw[0], raw[0], raw[0], a))
+                }
+                _ => true_color(raw, bpp, alpha_bits)
+            }
+        };
+
+        // The header alone can claim 1<<28 pixels, so nothing is reserved before the data is there:
+        // RLE output grows as packets are read, raw data is taken in one piece first.
+        let count = width as usize * height as usize;
+        let mut pixels = Vec::new();
+        if image_type & RLE != 0 {
+            while pixels.len() < count {
+                let packet = r.u8()?;
+                let length = (packet & 0x7f) as usize + 1;
+                if packet & 0x80 != 0 {
+                    let p = decode(r.take(pixel_bytes)?).ok_or_else(|| r.error("invalid pixel"))?;
+                    pixels.extend(std::iter::repeat_n(p, length));
+                } else {
+                    for _ in 0..length {
+                        pixels.push(decode(r.take(pixel_bytes)?).ok_or_else(|| r.error("invalid pixel"))?);
+                    }
+                }
+            }
+            pixels.truncate(count);
+        } else {
+            let raw = r.take(count * pixel_bytes)?;
+            pixels.reserve_exact(count);
+            for chunk in raw.chunks_exact(pixel_bytes) {
+                pixels.push(decode(chunk).ok_or_else(|| r.error("invalid pixel"))?);
+            }
+        }
+
+        let mut sprite = GsnSprite::new(width, height);
+        for (i, p) in pixels.into_iter().enumerate() {
+            let (x, y) = (i as u32 % width, i as u32 / width);
+            let x = if descriptor & RIGHT_TO_LEFT != 0 { width - 1 - x } else { x };
+            let y = if descriptor & TOP_TO_BOTTOM != 0 { y } else { height - 1 - y };
+            sprite.data[y as usize * width as usize + x as usize] = p;
+        }
+        Ok(sprite)
+    }
+
+    pub fn save_tga<P: AsRef<Path>>(&self, path: P) -> Result<(), GsnError> {
+        fs::write(path, self.to_tga_bytes()?)?;
+        Ok(())
+    }
+
+    // RLE compressed 32 bit BGRA, top left origin. Packets never cross a row.
+    // The header stores the size in 16 bits, larger sprites are an encode error.
+    pub fn to_tga_bytes(&self) -> Result<Vec<u8>, GsnError> {
+        let (Ok(width), Ok(height)) = (u16::try_from(self.width), u16::try_from(self.height)) else {
+            return Err(encode_error("tga", &format!("{}x{} does not fit in 16 bits", self.width, self.height)));
+        };
+        let mut out = Vec::with_capacity(18 + self.data.len() * 4);
+        out.extend_from_slice(&[0, 0, TRUE_COLOR | RLE, 0, 0, 0, 0, 0, 0, 0, 0, 0]);
+        out.extend_from_slice(&width.to_le_bytes());
+        out.extend_from_slice(&height.to_le_bytes());
+        out.extend_from_slice(&[32, 8 | TOP_TO_BOTTOM]);
+
+        let bgra = |p: &Pixel| [p.b(), p.g(), p.r(), p.a()];
+        for row in self.data.chunks(self.width.max(1) as usize) {
+            let mut i = 0;
+            while i < row.len() {
+                let run = row[i..].iter().take(128).take_while(|p| **p == row[i]).count();
+                if run > 1 {
+                    out.push(0x80 | (run - 1) as u8);
+                    out.extend_from_slice(&bgra(&row[i]));
+                    i += run;
+                    continue;
+                }
+                // Raw packet up to the next pair of equal pixels.
+                let mut end = i + 1;
+                while end < row.len() && end - i < 128 && !(end + 1 < row.len() && row[end] == row[end + 1]) {
+                    end += 1;
+                }
+                out.push((end - i - 1) as u8);
+                for p in &row[i..end] {
+                    out.extend_from_slice(&bgra(p));
+                }
+                i = end;
+            }
+        }
+        Ok(out)
+    }
+}
+
+fn true_color(raw: &[u8], bits: u8, alpha_bits: u8) -> Option<Pixel> {
+    match bits {
+        15 | 16 => {
+            let v = u16::from_le_bytes([raw[0], raw[1]]);
+            let five = |s: u16| (((v >> s) & 0x1f) * 255 / 31) as u8;
+            let a = if bits == 16 && alpha_bits > 0 && v & 0x8000 == 0 { 0 } else { 255 };
+            Some(pixel_rgba(five(10), five(5), five(0), a))
+        }
+        24 => Some(pixel_rgba(raw[2], raw[1], raw[0], 255)),
+        // Plenty of old tools write 32 bit pixels but leave the alpha bits at 0, those are opaque.
+        32 => Some(pixel_rgba(raw[2], raw[1], raw[0], if alpha_bits > 0 { raw[3] } else { 255 })),
+        _ => None
+    }
+}
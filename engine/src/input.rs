@@ -0,0 +1,209 @@
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::hash::Hash;
+use std::path::Path;
+use std::str::FromStr;
+use crate::error::GsnError;
+use crate::gamepad::GsnGamepadButton;
+use crate::{GsnAction, GsnButton, GsnKey};
+
+// What happened to a set of keys or buttons since the last update, held state lives with the engine.
+#[derive(Debug)]
+pub(crate) struct GsnEdges<T> {
+    pressed: HashSet<T>,
+    released: HashSet<T>,
+    repeated: HashSet<T>
+}
+
+impl<T: Eq + Hash + Copy> GsnEdges<T> {
+    pub(crate) fn new() -> GsnEdges<T> {
+        GsnEdges { pressed: HashSet::new(), released: HashSet::new(), repeated: HashSet::new() }
+    }
+
+    pub(crate) fn record(&mut self, input: T, action: &GsnAction) {
+        match action {
+            GsnAction::Press => self.pressed.insert(input),
+            GsnAction::Release => self.released.insert(input),
+            GsnAction::Repeat => self.repeated.insert(input)
+        };
+    }
+
+    pub(crate) fn roll_over(&mut self) {
+        self.pressed.clear();
+        self.released.clear();
+        self.repeated.clear();
+    }
+
+    pub(crate) fn pressed(&self, input: T) -> bool {
+        self.pressed.contains(&input)
+    }
+
+    pub(crate) fn released(&self, input: T) -> bool {
+        self.released.contains(&input)
+    }
+
+    pub(crate) fn repeated(&self, input: T) -> bool {
+        self.pressed.contains(&input) || self.repeated.contains(&input)
+    }
+
+    pub(crate) fn any_pressed<F: Fn(T) -> bool>(&self, matches: F) -> bool {
+        self.pressed.iter().any(|i| matches(*i))
+    }
+
+    pub(crate) fn any_released<F: Fn(T) -> bool>(&self, matches: F) -> bool {
+        self.released.iter().any(|i| matches(*i))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GsnBinding {
+    Key(GsnKey),
+    Mouse(GsnButton),
+    // The button on any connected pad.
+    Gamepad(GsnGamepadButton)
+}
+
+impl fmt::Display for GsnBinding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GsnBinding::Key(key) => write!(f, "key:{}", key),
+            GsnBinding::Mouse(button) => write!(f, "mouse:{}", button),
+            GsnBinding::Gamepad(button) => write!(f, "pad:{}", button)
+        }
+    }
+}
+
+impl FromStr for GsnBinding {
+    type Err = GsnError;
+
+    fn from_str(s: &str) -> Result<GsnBinding, GsnError> {
+        match s.split_once(':') {
+            Some(("key", key)) => Ok(GsnBinding::Key(key.parse()?)),
+            Some(("mouse", button)) => button.parse().map(GsnBinding::Mouse)
+                .map_err(|_| GsnError::InputMap(format!("invalid mouse button {}", button))),
+            Some(("pad", button)) => Ok(GsnBinding::Gamepad(button.parse()?)),
+            _ => Err(GsnError::InputMap(format!("invalid binding {}", s)))
+        }
+    }
+}
+
+impl fmt::Display for GsnKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+// Variant names, ignoring case.
+impl FromStr for GsnKey {
+    type Err = GsnError;
+
+    fn from_str(s: &str) -> Result<GsnKey, GsnError> {
+        GsnKey::ALL.iter().copied().find(|key| key.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| GsnError::InputMap(format!("unknown key {}", s)))
+    }
+}
+
+// Named actions bound to any number of inputs. The config format is one action per line,
+// `jump = key:Space mouse:1 pad:A`, with # starting a comment.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GsnInputMap {
+    actions: BTreeMap<String, Vec<GsnBinding>>
+}
+
+impl GsnInputMap {
+    pub fn new() -> GsnInputMap {
+        GsnInputMap::default()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<GsnInputMap, GsnError> {
+        fs::read_to_string(path)?.parse()
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), GsnError> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+
+    // Names that wouldn't survive a save and load are refused: empty ones, whitespace, `=` and `#`.
+    pub fn bind(&mut self, action: &str, binding: GsnBinding) -> Result<(), GsnError> {
+        check_action(action)?;
+        let bindings = self.actions.entry(action.to_string()).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+        Ok(())
+    }
+
+    pub fn unbind(&mut self, action: &str, binding: GsnBinding) {
+        if let Some(bindings) = self.actions.get_mut(action) {
+            bindings.retain(|b| *b != binding);
+        }
+    }
+
+    // Drops every binding but keeps the action, so it still shows up in a rebinding menu.
+    pub fn clear(&mut self, action: &str) {
+        if let Some(bindings) = self.actions.get_mut(action) {
+            bindings.clear();
+        }
+    }
+
+    pub fn remove(&mut self, action: &str) {
+        self.actions.remove(action);
+    }
+
+    pub fn bindings(&self, action: &str) -> &[GsnBinding] {
+        self.actions.get(action).map_or(&[], |b| b.as_slice())
+    }
+
+    pub fn actions(&self) -> impl Iterator<Item = &str> {
+        self.actions.keys().map(|a| a.as_str())
+    }
+}
+
+fn check_action(action: &str) -> Result<(), GsnError> {
+    if action.is_empty() || action.contains(|c: char| c.is_whitespace() || c == '=' || c == '#') {
+        return Err(GsnError::InputMap(format!("invalid action name {:?}", action)));
+    }
+    Ok(())
+}
+
+impl fmt::Display for GsnInputMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (action, bindings) in &self.actions {
+            write!(f, "{} =", action)?;
+            for binding in bindings {
+                write!(f, " {}", binding)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for GsnInputMap {
+    type Err = GsnError;
+
+    fn from_str(s: &str) -> Result<GsnInputMap, GsnError> {
+        let mut map = GsnInputMap::new();
+        for (i, line) in s.lines().enumerate() {
+            let line = line.split('#').next().unwrap_or("").trim();
+            if line.is_empty() {
+                continue;
+            }
+            let line_error = |e: GsnError| match e {
+                GsnError::InputMap(message) => GsnError::InputMap(format!("line {}: {}", i + 1, message)),
+                e => e
+            };
+            let (action, bindings) = line.split_once('=')
+                .ok_or_else(|| line_error(GsnError::InputMap("expected action = bindings".to_string())))?;
+            let action = action.trim();
+            check_action(action).map_err(line_error)?;
+            map.actions.entry(action.to_string()).or_default();
+            for binding in bindings.split_whitespace() {
+                map.bind(action, binding.parse().map_err(line_error)?)?;
+            }
+        }
+        Ok(map)
+    }
+}
@@ -1,9 +1,28 @@
 pub mod renderer;
+pub mod sprite;
+mod draw;
+pub mod blit;
+pub mod blend;
+pub mod error;
+pub mod font;
+mod image;
+pub mod headless;
+pub mod window;
+pub mod input;
+pub mod gamepad;
+pub mod time;
+pub mod app;
 
 use std::collections::HashMap;
-use std::sync::mpsc::Receiver;
-use glfw::{Context, Glfw, Key};
-use crate::renderer::{GsnSprite, Pixel};
+use std::path::PathBuf;
+use glfw::Key;
+use crate::renderer::{letterbox, GsnBackend, GsnResizePolicy, GsnScaleMode, GsnSprite, GsnUploadMode, GsnUploadStats, GsnViewport, Pixel};
+use crate::window::{GsnWindow, GsnWindowSettings};
+use crate::time::GsnClock;
+use crate::app::GsnApp;
+use crate::error::GsnError;
+use crate::input::{GsnBinding, GsnEdges, GsnInputMap};
+use crate::gamepad::{GlfwGamepads, GsnGamepad, GsnGamepadAxis, GsnGamepadButton, GsnGamepadSource};
 
 
 pub mod engine {
@@ -11,33 +30,63 @@ pub mod engine {
 }
 
 pub struct GsnEngine {
-    glfw: Glfw,
-    pub window: glfw::Window,
-    pub events: Receiver<(f64,glfw::WindowEvent)>,
-    pub renderer: renderer::GsnRenderer,
+    pub window: Option<GsnWindow>,
+    pub renderer: Box<dyn GsnBackend>,
     pub actions: Vec<GsnEvent>,
-    pub keys_held: HashMap<GsnKey,bool>
+    pub keys_held: HashMap<GsnKey,bool>,
+    pub buttons_held: HashMap<GsnButton,bool>,
+    pub input_map: GsnInputMap,
+    keys: GsnEdges<GsnKey>,
+    buttons: GsnEdges<GsnButton>,
+    // Sticks and triggers read as 0 inside this, see GsnGamepad::axis.
+    pub gamepad_deadzone: f32,
+    gamepad_source: Option<Box<dyn GsnGamepadSource>>,
+    gamepads: Vec<GsnGamepad>,
+    pad_buttons: GsnEdges<(usize, GsnGamepadButton)>,
+    modifiers: GsnModifiers,
+    pub clock: GsnClock,
+    scale_mode: GsnScaleMode,
+    resize_policy: GsnResizePolicy,
+    upload_mode: GsnUploadMode,
+    // Framebuffer size in pixels, the buffer is letterboxed into it.
+    output: (u32, u32),
+    closing: bool,
+    frames: u64
 }
 
 //noinspection ALL
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
 pub struct GsnModifiers {
-    shift: bool,
-    alt: bool,
-    control: bool,
-    _super: bool,
-    capslock: bool,
-    numlock: bool
+    pub shift: bool,
+    pub alt: bool,
+    pub control: bool,
+    pub _super: bool,
+    pub capslock: bool,
+    pub numlock: bool
 }
 
 #[derive(Debug)]
 pub enum GsnEvent {
     Init,
     Draw,
-    KeyPress(GsnKey, GsnAction),
-    MousePress(GsnButton, GsnAction, MousePos)
+    KeyPress(GsnKey, GsnAction, GsnModifiers),
+    MousePress(GsnButton, GsnAction, MousePos, GsnModifiers),
+    CursorMove(MousePos),
+    // Horizontal and vertical wheel offsets, one notch is 1.0.
+    Scroll(f64, f64),
+    // Text input after keyboard layout and dead keys, use this rather than KeyPress for text fields.
+    Char(char),
+    Focus(bool),
+    // The window's new framebuffer size in pixels. Under GsnResizePolicy::Grow the buffer already has it.
+    Resized(u32, u32),
+    CloseRequested,
+    FileDrop(Vec<PathBuf>),
+    GamepadConnected(usize),
+    GamepadDisconnected(usize)
 }
 
-#[derive(Debug)]
+// In buffer pixels, see GsnEngine::window_to_buffer.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct MousePos {
     pub x: u32,
     pub y: u32
@@ -66,95 +115,537 @@ pub enum GsnKey {
     RightAlt,RightSuper,Menu
 }
 
-type GsnButton = u8;
+impl GsnKey {
+    // Every key, in declaration order. Key names in config files are these variant names.
+    pub const ALL: [GsnKey; 121] = [
+        GsnKey::Escape, GsnKey::Space, GsnKey::Up, GsnKey::Down, GsnKey::Left, GsnKey::Right, GsnKey::Apostrophe, GsnKey::Comma, GsnKey::Minus, GsnKey::Period, GsnKey::Slash,
+        GsnKey::A, GsnKey::B, GsnKey::C, GsnKey::D, GsnKey::E, GsnKey::F, GsnKey::G, GsnKey::H, GsnKey::I, GsnKey::J, GsnKey::K, GsnKey::L, GsnKey::M, GsnKey::N, GsnKey::O, GsnKey::P, GsnKey::Q, GsnKey::R, GsnKey::S, GsnKey::T, GsnKey::U, GsnKey::V, GsnKey::W, GsnKey::X, GsnKey::Y, GsnKey::Z,
+        GsnKey::Unknown,
+        GsnKey::Num0, GsnKey::Num1, GsnKey::Num2, GsnKey::Num3, GsnKey::Num4, GsnKey::Num5, GsnKey::Num6, GsnKey::Num7, GsnKey::Num8, GsnKey::Num9,
+        GsnKey::Kp0, GsnKey::Kp1, GsnKey::Kp2, GsnKey::Kp3, GsnKey::Kp4, GsnKey::Kp5, GsnKey::Kp6, GsnKey::Kp7, GsnKey::Kp8, GsnKey::Kp9,
+        GsnKey::F1, GsnKey::F2, GsnKey::F3, GsnKey::F4, GsnKey::F5, GsnKey::F6, GsnKey::F7, GsnKey::F8, GsnKey::F9, GsnKey::F10, GsnKey::F11, GsnKey::F12, GsnKey::F13, GsnKey::F14, GsnKey::F15, GsnKey::F16, GsnKey::F17, GsnKey::F18, GsnKey::F19, GsnKey::F20, GsnKey::F21, GsnKey::F22, GsnKey::F23, GsnKey::F24, GsnKey::F25,
+        GsnKey::Enter, GsnKey::Grave, GsnKey::LeftBracket, GsnKey::RightBracket, GsnKey::SemiColon, GsnKey::Backslash,
+        GsnKey::Equal, GsnKey::World1, GsnKey::World2, GsnKey::Tab, GsnKey::Backspace, GsnKey::Insert, GsnKey::Delete, GsnKey::PageUp, GsnKey::PageDown,
+        GsnKey::Home, GsnKey::End, GsnKey::CapsLock, GsnKey::ScrollLock, GsnKey::NumLock, GsnKey::PrintScreen, GsnKey::Pause,
+        GsnKey::KpDecimal, GsnKey::KpDivide, GsnKey::KpMultiply, GsnKey::KpSubtract, GsnKey::KpAdd, GsnKey::KpEnter, GsnKey::KpEqual,
+        GsnKey::LeftShift, GsnKey::LeftControl, GsnKey::LeftAlt, GsnKey::LeftSuper, GsnKey::RightShift, GsnKey::RightControl,
+        GsnKey::RightAlt, GsnKey::RightSuper, GsnKey::Menu
+    ];
+}
+
+pub type GsnButton = u8;
 
+// Fullscreen modes take a monitor index, 0 is the primary monitor.
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum GsnWindowMode {
-    WINDOWED
+    WINDOWED,
+    // Exclusive fullscreen at the monitor's current video mode.
+    FULLSCREEN(usize),
+    // An undecorated window covering the monitor, quicker to alt-tab out of.
+    BORDERLESS(usize)
+}
+
+pub struct GsnEngineBuilder {
+    settings: GsnWindowSettings,
+    logical_size: Option<(u32, u32)>,
+    scale_mode: GsnScaleMode,
+    resize_policy: GsnResizePolicy,
+    upload_mode: GsnUploadMode
+}
+
+impl GsnEngineBuilder {
+    pub fn mode(mut self, mode: GsnWindowMode) -> GsnEngineBuilder {
+        self.settings.mode = mode;
+        self
+    }
+
+    pub fn resizable(mut self, resizable: bool) -> GsnEngineBuilder {
+        self.settings.resizable = resizable;
+        self
+    }
+
+    pub fn vsync(mut self, vsync: bool) -> GsnEngineBuilder {
+        self.settings.vsync = vsync;
+        self
+    }
+
+    // Size of the buffer games draw into, scaled up to the window. Defaults to the window size.
+    pub fn logical_size(mut self, width: u32, height: u32) -> GsnEngineBuilder {
+        self.logical_size = Some((width, height));
+        self
+    }
+
+    pub fn scale_mode(mut self, mode: GsnScaleMode) -> GsnEngineBuilder {
+        self.scale_mode = mode;
+        self
+    }
+
+    pub fn resize_policy(mut self, policy: GsnResizePolicy) -> GsnEngineBuilder {
+        self.resize_policy = policy;
+        self
+    }
+
+    pub fn upload_mode(mut self, mode: GsnUploadMode) -> GsnEngineBuilder {
+        self.upload_mode = mode;
+        self
+    }
+
+    pub fn build(self) -> Result<GsnEngine, GsnError> {
+        let settings = self.settings;
+        let mut window = window::create_window(&settings)?;
+        gl::load_with(|s| window.window.get_proc_address(s));
+        let gamepads = GlfwGamepads { glfw: window.glfw.clone() };
+        let (width, height) = self.logical_size.unwrap_or((settings.width, settings.height));
+        let (output_width, output_height) = window.window.get_framebuffer_size();
+        let mut renderer = renderer::new_gsn_renderer();
+        renderer.initialize(width, height)?;
+        let mut engine = GsnEngine::with_backend(Some(window), Box::new(renderer), width, height);
+        engine.set_gamepad_source(Box::new(gamepads));
+        engine.resize_policy = self.resize_policy;
+        engine.set_upload_mode(self.upload_mode);
+        engine.scale_mode = self.scale_mode;
+        engine.fit_output(output_width.max(0) as u32, output_height.max(0) as u32);
+        Ok(engine)
+    }
 }
 
 impl GsnEngine {
-    pub fn new(title: &str, width: u32, height: u32, mode: GsnWindowMode) -> GsnEngine {
+    pub fn new(title: &str, width: u32, height: u32, mode: GsnWindowMode) -> Result<GsnEngine, GsnError> {
+        GsnEngine::builder(title, width, height).mode(mode).build()
+    }
+
+    // Windowed, not resizable, vsync on unless changed.
+    pub fn builder(title: &str, width: u32, height: u32) -> GsnEngineBuilder {
+        GsnEngineBuilder {
+            settings: GsnWindowSettings {
+                title: title.to_string(),
+                width,
+                height,
+                mode: GsnWindowMode::WINDOWED,
+                resizable: false,
+                vsync: true
+            },
+            logical_size: None,
+            scale_mode: GsnScaleMode::default(),
+            resize_policy: GsnResizePolicy::default(),
+            upload_mode: GsnUploadMode::default()
+        }
+    }
+
+    // Can't fail, nothing outside the process is involved.
+    pub fn headless(width: u32, height: u32) -> GsnEngine {
+        GsnEngine::with_backend(None, Box::new(headless::new_gsn_headless_sized(width, height)), width, height)
+    }
+
+    // The renderer is already initialized at width x height.
+    fn with_backend(window: Option<GsnWindow>, renderer: Box<dyn GsnBackend>, width: u32, height: u32) -> GsnEngine {
         let actions = vec![GsnEvent::Init];
-        let glfw = glfw::init(glfw::FAIL_ON_ERRORS).unwrap();
-        let (mut window, events) = glfw.create_window(
-            width,
-            height,
-            title,
-            match mode {
-                GsnWindowMode::WINDOWED => glfw::WindowMode::Windowed
-            }).expect("Failed to create GLFW window.");
-        gl::load_with(|s| window.get_proc_address(s));
-        let mut renderer = renderer::new_gsn_renderer();
-        renderer.initialize(width,height);
-        window.make_current();
-        window.set_key_polling(true);
-        window.set_mouse_button_polling(true);
         let keys_held: HashMap<GsnKey,bool> = HashMap::new();
 
         GsnEngine {
-            glfw,
             window,
-            events,
             renderer,
             actions,
-            keys_held
+            keys_held,
+            buttons_held: HashMap::new(),
+            input_map: GsnInputMap::new(),
+            keys: GsnEdges::new(),
+            buttons: GsnEdges::new(),
+            gamepad_deadzone: 0.2,
+            gamepad_source: None,
+            gamepads: vec![],
+            pad_buttons: GsnEdges::new(),
+            modifiers: GsnModifiers::default(),
+            clock: GsnClock::new(),
+            scale_mode: GsnScaleMode::default(),
+            resize_policy: GsnResizePolicy::default(),
+            upload_mode: GsnUploadMode::default(),
+            output: (width, height),
+            closing: false,
+            frames: 0
         }
     }
 
     pub fn render(&mut self) {
-        self.actions.push(GsnEvent::Draw);
         self.renderer.render();
-        self.window.swap_buffers();
+        if let Some(window) = self.window.as_mut() {
+            window.swap_buffers();
+        }
+        self.frames += 1;
     }
 
+    // Shown the way GsnSprite stores it, row 0 at the top of the window and y growing down,
+    // which is also how window_to_buffer reports the cursor.
     pub fn buffer(&mut self) -> &mut GsnSprite {
-        &mut self.renderer.buffer
+        self.renderer.buffer_mut()
     }
 
     pub fn width(&self) -> u32 {
-        self.renderer.buffer.width
+        self.renderer.buffer().width
     }
 
     pub fn height(&self) -> u32 {
-        self.renderer.buffer.height
+        self.renderer.buffer().height
+    }
+
+    pub fn frames(&self) -> u64 {
+        self.frames
+    }
+
+    pub fn upload_stats(&self) -> GsnUploadStats {
+        self.renderer.upload_stats()
+    }
+
+    pub fn upload_mode(&self) -> GsnUploadMode {
+        self.upload_mode
+    }
+
+    // Headless engines keep the setting but have nothing to stream.
+    pub fn set_upload_mode(&mut self, mode: GsnUploadMode) {
+        self.upload_mode = mode;
+        self.renderer.set_upload_mode(mode);
     }
 
     pub fn exit(&mut self) {
-        self.window.set_should_close(true);
+        self.closing = true;
+        if let Some(window) = self.window.as_mut() {
+            window.window.set_should_close(true);
+        }
     }
 
+    pub fn scale_mode(&self) -> GsnScaleMode {
+        self.scale_mode
+    }
+
+    pub fn set_scale_mode(&mut self, mode: GsnScaleMode) {
+        self.scale_mode = mode;
+        let viewport = self.viewport();
+        self.renderer.set_viewport(self.output.0, self.output.1, viewport);
+    }
+
+    pub fn resize_policy(&self) -> GsnResizePolicy {
+        self.resize_policy
+    }
+
+    // Takes effect on the next resize.
+    pub fn set_resize_policy(&mut self, policy: GsnResizePolicy) {
+        self.resize_policy = policy;
+    }
+
+    // Called when the window's framebuffer changes size, headless engines can call it to act as if they had
+    // a window of that size. Pushes a Resized event.
+    pub fn resize(&mut self, output_width: u32, output_height: u32) {
+        self.fit_output(output_width, output_height);
+        self.push_event(GsnEvent::Resized(output_width, output_height));
+    }
+
+    // A minimized window reports 0x0, the buffer is left alone rather than reallocated to nothing.
+    fn fit_output(&mut self, output_width: u32, output_height: u32) {
+        self.output = (output_width, output_height);
+        let grow = self.resize_policy == GsnResizePolicy::Grow && output_width > 0 && output_height > 0;
+        if grow && (output_width, output_height) != (self.width(), self.height()) {
+            self.renderer.resize(output_width, output_height);
+        }
+        self.set_scale_mode(self.scale_mode);
+    }
+
+    // Where the buffer sits in the window's framebuffer.
+    pub fn viewport(&self) -> GsnViewport {
+        letterbox(self.width(), self.height(), self.output.0, self.output.1, self.scale_mode)
+    }
+
+    // Also the letterbox color.
+    pub fn set_clear_color(&mut self, color: Pixel) {
+        self.renderer.set_clear_color(color);
+    }
+
+    // Window coordinates, as glfw reports the cursor, to a buffer pixel. Both count y down from the top.
+    // Points over the letterbox clamp to the nearest edge of the buffer.
+    pub fn window_to_buffer(&self, x: f64, y: f64) -> MousePos {
+        let (window_width, window_height) = self.window.as_ref().map_or(self.output, |w| {
+            let (w, h) = w.window.get_size();
+            (w.max(1) as u32, h.max(1) as u32)
+        });
+        let v = self.viewport();
+        let fx = x * self.output.0 as f64 / window_width.max(1) as f64;
+        let fy = y * self.output.1 as f64 / window_height.max(1) as f64;
+        let bx = ((fx - v.x as f64) * self.width() as f64 / v.width.max(1) as f64).floor();
+        let by = ((fy - v.y as f64) * self.height() as f64 / v.height.max(1) as f64).floor();
+        MousePos {
+            x: bx.clamp(0.0, self.width().saturating_sub(1) as f64) as u32,
+            y: by.clamp(0.0, self.height().saturating_sub(1) as f64) as u32
+        }
+    }
+
+    // Headless engines are always WINDOWED and ignore the window setters.
+    pub fn window_mode(&self) -> GsnWindowMode {
+        self.window.as_ref().map_or(GsnWindowMode::WINDOWED, |w| w.mode())
+    }
+
+    pub fn set_window_mode(&mut self, mode: GsnWindowMode) {
+        if let Some(window) = self.window.as_mut() {
+            window.set_mode(mode);
+        }
+    }
+
+    pub fn set_vsync(&mut self, vsync: bool) {
+        if let Some(window) = self.window.as_mut() {
+            window.set_vsync(vsync);
+        }
+    }
+
+    pub fn set_resizable(&mut self, resizable: bool) {
+        if let Some(window) = self.window.as_mut() {
+            window.set_resizable(resizable);
+        }
+    }
+
+    pub fn should_close(&self) -> bool {
+        self.closing || self.window.as_ref().is_some_and(|w| w.window.should_close())
+    }
+
+    // Pressed and released answer for the events since the previous update, so that state rolls over first.
+    // For hand written loops draining actions: one Draw is queued after the frame's input.
     pub fn update(&mut self) {
-        self.glfw.poll_events();
+        self.poll();
+        self.actions.push(GsnEvent::Draw);
+        self.render();
+    }
+
+    // Ticks the clock and turns window and gamepad input into events, without rendering.
+    fn poll(&mut self) {
+        self.clock.tick();
+        self.keys.roll_over();
+        self.buttons.roll_over();
+        self.pad_buttons.roll_over();
+        let events = match self.window.as_mut() {
+            Some(window) => window.poll_events(),
+            None => vec![],
+        };
 
-        for (_, event) in glfw::flush_messages(&self.events) {
+        for event in events {
             match event {
-                glfw::WindowEvent::Key(key, _, action, _) => {
-                    let gsn_key = map_keys(key);
-                    let gsn_action = map_action(action);
-                    match gsn_action {
-                        GsnAction::Press => {self.keys_held.insert(gsn_key,true);}
-                        GsnAction::Release => {self.keys_held.insert(gsn_key,false);}
-                        _ => {}
-                    }
-                    self.actions.push(GsnEvent::KeyPress(gsn_key, gsn_action));
+                glfw::WindowEvent::Key(key, _, action, modifiers) => {
+                    self.push_event(GsnEvent::KeyPress(map_keys(key), map_action(action), map_modifiers(modifiers)));
                 },
                 glfw::WindowEvent::MouseButton(button,action, modifiers) => {
-                    let (mx,my) = self.window.get_cursor_pos();
+                    let (mx,my) = self.window.as_ref().map_or((0.0, 0.0), |w| w.window.get_cursor_pos());
                     let gsn_button: GsnButton = map_mouse_button(button);
                     let gsn_action = map_action(action);
-                    self.actions.push(GsnEvent::MousePress(gsn_button,gsn_action,MousePos{x: mx as u32,y: my as u32}));
+                    let position = self.window_to_buffer(mx, my);
+                    self.push_event(GsnEvent::MousePress(gsn_button,gsn_action,position,map_modifiers(modifiers)));
+                },
+                glfw::WindowEvent::CursorPos(x, y) => {
+                    let position = self.window_to_buffer(x, y);
+                    self.push_event(GsnEvent::CursorMove(position));
                 },
+                glfw::WindowEvent::Scroll(x, y) => self.push_event(GsnEvent::Scroll(x, y)),
+                glfw::WindowEvent::Char(c) => self.push_event(GsnEvent::Char(c)),
+                glfw::WindowEvent::Focus(focused) => self.push_event(GsnEvent::Focus(focused)),
+                glfw::WindowEvent::FramebufferSize(w, h) => self.resize(w.max(0) as u32, h.max(0) as u32),
+                glfw::WindowEvent::Close => self.push_event(GsnEvent::CloseRequested),
+                glfw::WindowEvent::FileDrop(paths) => self.push_event(GsnEvent::FileDrop(paths)),
                 _ => {},
             }
         }
-        self.actions.push(GsnEvent::Draw);
-        self.render();
+        self.poll_gamepads();
+    }
+
+    // Owns the loop: every frame polls, hands the app each event in arrival order, updates it and renders.
+    // Ends once the window closes or exit() is called, checked after events and after on_update.
+    pub fn run<A: GsnApp>(&mut self, app: &mut A) {
+        app.on_create(self);
+        while !self.should_close() {
+            self.poll();
+            for event in std::mem::take(&mut self.actions) {
+                app.on_event(self, &event);
+            }
+            if self.should_close() {
+                break;
+            }
+            let dt = self.dt();
+            app.on_update(self, dt);
+            if self.should_close() {
+                break;
+            }
+            self.render();
+        }
+        app.on_destroy(self);
+    }
+
+    // Seconds since the previous update.
+    pub fn dt(&self) -> f64 {
+        self.clock.dt()
+    }
+
+    // run with a closure as on_update, returning false is the same as exit(). Events are dispatched to nobody,
+    // read input through key_held, key_pressed and the other state queries.
+    pub fn run_loop<F: FnMut(&mut GsnEngine, f64) -> bool>(&mut self, frame: F) {
+        struct Frame<F>(F);
+        impl<F: FnMut(&mut GsnEngine, f64) -> bool> GsnApp for Frame<F> {
+            fn on_update(&mut self, engine: &mut GsnEngine, dt: f64) {
+                if !(self.0)(engine, dt) {
+                    engine.exit();
+                }
+            }
+        }
+        self.run(&mut Frame(frame));
+    }
+
+    // Replaces where pads come from, the windowed engine starts out reading glfw's and a headless one has none.
+    pub fn set_gamepad_source(&mut self, source: Box<dyn GsnGamepadSource>) {
+        self.gamepad_source = Some(source);
+    }
+
+    // Diffs the source against the last poll, connects and disconnects go into actions in id order.
+    fn poll_gamepads(&mut self) {
+        let Some(source) = self.gamepad_source.as_mut() else { return };
+        let mut states = source.poll();
+        states.sort_by_key(|s| s.id);
+        states.dedup_by_key(|s| s.id);
+
+        for pad in &self.gamepads {
+            if !states.iter().any(|s| s.id == pad.id()) {
+                for button in GsnGamepadButton::ALL.into_iter().filter(|b| pad.button(*b)) {
+                    self.pad_buttons.record((pad.id(), button), &GsnAction::Release);
+                }
+                self.actions.push(GsnEvent::GamepadDisconnected(pad.id()));
+            }
+        }
+        let mut gamepads = Vec::with_capacity(states.len());
+        for state in states {
+            let previous = self.gamepads.iter().find(|p| p.id() == state.id);
+            if previous.is_none() {
+                self.actions.push(GsnEvent::GamepadConnected(state.id));
+            }
+            for button in GsnGamepadButton::ALL {
+                let was_down = previous.is_some_and(|p| p.button(button));
+                match (was_down, state.buttons[button as usize]) {
+                    (false, true) => self.pad_buttons.record((state.id, button), &GsnAction::Press),
+                    (true, false) => self.pad_buttons.record((state.id, button), &GsnAction::Release),
+                    _ => {}
+                }
+            }
+            gamepads.push(GsnGamepad { state, deadzone: self.gamepad_deadzone });
+        }
+        self.gamepads = gamepads;
+    }
+
+    // Entry point for input, both translated window events and synthetic ones from a headless test.
+    pub fn push_event(&mut self, event: GsnEvent) {
+        match &event {
+            GsnEvent::KeyPress(key, action, modifiers) => {
+                match action {
+                    GsnAction::Press => {self.keys_held.insert(*key,true);}
+                    GsnAction::Release => {self.keys_held.insert(*key,false);}
+                    _ => {}
+                }
+                self.keys.record(*key, action);
+                self.modifiers = *modifiers;
+            }
+            GsnEvent::MousePress(button, action, _, modifiers) => {
+                match action {
+                    GsnAction::Press => {self.buttons_held.insert(*button,true);}
+                    GsnAction::Release => {self.buttons_held.insert(*button,false);}
+                    _ => {}
+                }
+                self.buttons.record(*button, action);
+                self.modifiers = *modifiers;
+            }
+            _ => {}
+        }
+        self.actions.push(event);
     }
 
     pub fn key_held(&self, key: GsnKey) -> bool {
         *self.keys_held.get(&key).unwrap_or(&false)
     }
 
+    pub fn key_pressed(&self, key: GsnKey) -> bool {
+        self.keys.pressed(key)
+    }
+
+    pub fn key_released(&self, key: GsnKey) -> bool {
+        self.keys.released(key)
+    }
+
+    // Pressed or auto repeated by the OS since the last update, for menus and text cursors.
+    pub fn key_repeated(&self, key: GsnKey) -> bool {
+        self.keys.repeated(key)
+    }
+
+    pub fn mouse_held(&self, button: GsnButton) -> bool {
+        *self.buttons_held.get(&button).unwrap_or(&false)
+    }
+
+    pub fn mouse_pressed(&self, button: GsnButton) -> bool {
+        self.buttons.pressed(button)
+    }
+
+    pub fn mouse_released(&self, button: GsnButton) -> bool {
+        self.buttons.released(button)
+    }
+
+    pub fn gamepads(&self) -> &[GsnGamepad] {
+        &self.gamepads
+    }
+
+    pub fn gamepad(&self, id: usize) -> Option<&GsnGamepad> {
+        self.gamepads.iter().find(|p| p.id() == id)
+    }
+
+    pub fn gamepad_held(&self, id: usize, button: GsnGamepadButton) -> bool {
+        self.gamepad(id).is_some_and(|p| p.button(button))
+    }
+
+    pub fn gamepad_pressed(&self, id: usize, button: GsnGamepadButton) -> bool {
+        self.pad_buttons.pressed((id, button))
+    }
+
+    pub fn gamepad_released(&self, id: usize, button: GsnGamepadButton) -> bool {
+        self.pad_buttons.released((id, button))
+    }
+
+    // 0 for a pad that isn't connected.
+    pub fn gamepad_axis(&self, id: usize, axis: GsnGamepadAxis) -> f32 {
+        self.gamepad(id).map_or(0.0, |p| p.axis(axis))
+    }
+
+    // Actions resolve through input_map, an unknown action is never held.
+    pub fn action_held(&self, action: &str) -> bool {
+        self.input_map.bindings(action).iter().any(|b| self.binding_held(*b))
+    }
+
+    pub fn action_pressed(&self, action: &str) -> bool {
+        self.input_map.bindings(action).iter().any(|b| match *b {
+            GsnBinding::Key(key) => self.key_pressed(key),
+            GsnBinding::Mouse(button) => self.mouse_pressed(button),
+            GsnBinding::Gamepad(button) => self.pad_buttons.any_pressed(|(_, b)| b == button)
+        })
+    }
+
+    // Released since the last update with nothing else bound to the action still down.
+    pub fn action_released(&self, action: &str) -> bool {
+        !self.action_held(action) && self.input_map.bindings(action).iter().any(|b| match *b {
+            GsnBinding::Key(key) => self.key_released(key),
+            GsnBinding::Mouse(button) => self.mouse_released(button),
+            GsnBinding::Gamepad(button) => self.pad_buttons.any_released(|(_, b)| b == button)
+        })
+    }
+
+    fn binding_held(&self, binding: GsnBinding) -> bool {
+        match binding {
+            GsnBinding::Key(key) => self.key_held(key),
+            GsnBinding::Mouse(button) => self.mouse_held(button),
+            GsnBinding::Gamepad(button) => self.gamepads.iter().any(|p| p.button(button))
+        }
+    }
+
+    // Modifier state as of the last key or mouse button event.
+    pub fn modifiers(&self) -> GsnModifiers {
+        self.modifiers
+    }
+
 }
 
 fn map_action(glfw_action: glfw::Action) -> GsnAction {
@@ -164,6 +655,16 @@ fn map_action(glfw_action: glfw::Action) -> GsnAction {
         glfw::Action::Repeat => GsnAction::Repeat
     }
 }
+fn map_modifiers(glfw_modifiers: glfw::Modifiers) -> GsnModifiers {
+    GsnModifiers {
+        shift: glfw_modifiers.contains(glfw::Modifiers::Shift),
+        alt: glfw_modifiers.contains(glfw::Modifiers::Alt),
+        control: glfw_modifiers.contains(glfw::Modifiers::Control),
+        _super: glfw_modifiers.contains(glfw::Modifiers::Super),
+        capslock: glfw_modifiers.contains(glfw::Modifiers::CapsLock),
+        numlock: glfw_modifiers.contains(glfw::Modifiers::NumLock)
+    }
+}
 fn map_mouse_button(glfw_mouse_button: glfw::MouseButton) -> GsnButton {
     match glfw_mouse_button {
         glfw::MouseButton::Button1 => 1,
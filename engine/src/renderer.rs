@@ -1,4 +1,7 @@
 use std::mem::{size_of, size_of_val};
+use crate::blit::GsnRect;
+use crate::error::GsnError;
+pub use crate::sprite::{GsnSprite, Pixel, pixel_rgb, pixel_rgba, WHITE, BLACK, RED, GREEN, BLUE};
 
 const VERT_SHADER: &str = r##"
     #version 330 core
@@ -29,6 +32,107 @@ const FRAG_SHADER: &str = r##"
     }
     "##;
 
+// How the buffer is fitted to a window of a different size.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum GsnScaleMode {
+    // Largest whole multiple that fits, every buffer pixel becomes an equal square. Falls back to Fit
+    // when the window is smaller than the buffer.
+    Integer,
+    // Largest size that keeps the aspect ratio.
+    #[default]
+    Fit,
+    // Fill the window, whatever the aspect ratio.
+    Stretch
+}
+
+// What happens to the buffer when the window's framebuffer changes size.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum GsnResizePolicy {
+    // The buffer stays at its size and is scaled into the new window.
+    #[default]
+    Keep,
+    // The buffer is reallocated at the framebuffer size, one buffer pixel per screen pixel. Its contents
+    // are lost, so redraw everything after a Resized event.
+    Grow
+}
+
+// Where the buffer lands in the window, in framebuffer pixels from the top left. The rest is letterbox.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GsnViewport {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32
+}
+
+pub fn letterbox(buffer_width: u32, buffer_height: u32, output_width: u32, output_height: u32, mode: GsnScaleMode) -> GsnViewport {
+    let (bw, bh) = (buffer_width.max(1) as f64, buffer_height.max(1) as f64);
+    let (ow, oh) = (output_width as f64, output_height as f64);
+    let fit = (ow / bw).min(oh / bh);
+    let scale = match mode {
+        GsnScaleMode::Stretch => return GsnViewport { x: 0, y: 0, width: output_width, height: output_height },
+        GsnScaleMode::Integer if fit >= 1.0 => fit.floor(),
+        _ => fit
+    };
+    let (width, height) = ((bw * scale).round() as u32, (bh * scale).round() as u32);
+    GsnViewport {
+        x: (output_width as i32 - width as i32) / 2,
+        y: (output_height as i32 - height as i32) / 2,
+        width,
+        height
+    }
+}
+
+// How the buffer reaches the texture.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum GsnUploadMode {
+    // Straight from the buffer with TexSubImage2D, simple but the driver may stall until the copy is done.
+    #[default]
+    Direct,
+    // Through a ring of pixel buffer objects, so the GPU can still be reading one frame while the next is
+    // written. Persistently mapped on GL 4.4 or with GL_ARB_buffer_storage, mapped per upload otherwise.
+    Streaming
+}
+
+// Pixel buffers in the streaming ring.
+const STREAM_BUFFERS: usize = 3;
+
+// Running totals of what the backend sent to the GPU, headless counts what it would have sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GsnUploadStats {
+    // Frames that sent the whole buffer.
+    pub full_uploads: u64,
+    // Frames that sent only the changed rectangles.
+    pub partial_uploads: u64,
+    // Frames where nothing had changed.
+    pub skipped_uploads: u64,
+    pub rects: u64,
+    pub pixels: u64
+}
+
+// Past this share of the buffer one full upload is cheaper than several small ones.
+const FULL_UPLOAD_RATIO: f64 = 0.5;
+
+// Takes the sprite's dirty rectangles and decides what to send, counting it in stats.
+pub(crate) fn plan_upload(sprite: &mut GsnSprite, stats: &mut GsnUploadStats) -> Vec<GsnRect> {
+    let rects: Vec<GsnRect> = sprite.take_dirty().into_iter().filter(|r| r.area() > 0).collect();
+    let total = sprite.width as u64 * sprite.height as u64;
+    let changed: u64 = rects.iter().map(|r| r.area()).sum();
+    let rects = if rects.is_empty() {
+        stats.skipped_uploads += 1;
+        rects
+    } else if changed as f64 >= total as f64 * FULL_UPLOAD_RATIO {
+        stats.full_uploads += 1;
+        vec![GsnRect::new(0, 0, sprite.width, sprite.height)]
+    } else {
+        stats.partial_uploads += 1;
+        rects
+    };
+    stats.rects += rects.len() as u64;
+    stats.pixels += rects.iter().map(|r| r.area()).sum::<u64>();
+    rects
+}
+
 pub struct GsnRenderer {
     vao: u32,
     vbo: u32,
@@ -38,9 +142,26 @@ pub struct GsnRenderer {
     clear_color: Pixel,
     width: u32,
     height: u32,
+    output_width: u32,
+    output_height: u32,
+    viewport: GsnViewport,
+    upload_stats: GsnUploadStats,
+    upload_mode: GsnUploadMode,
+    stream: Option<GsnStream>,
     pub(crate) buffer: GsnSprite,
 }
 
+// The streaming ring, each pixel buffer laid out like the whole sprite so rectangles keep their offsets.
+struct GsnStream {
+    buffers: [u32; STREAM_BUFFERS],
+    // Null unless persistently mapped.
+    mapped: [*mut u8; STREAM_BUFFERS],
+    // Signalled once the GPU has finished reading that buffer.
+    fences: [gl::types::GLsync; STREAM_BUFFERS],
+    next: usize,
+    size: usize
+}
+
 
 pub fn new_gsn_renderer() -> GsnRenderer {
     let gsn_renderer = GsnRenderer {
@@ -52,116 +173,62 @@ pub fn new_gsn_renderer() -> GsnRenderer {
         clear_color: pixel_rgb(0,0,0),
         width: 0,
         height: 0,
-        buffer: GsnSprite {
-            width: 0,
-            height: 0,
-            data: vec![]
-        },
+        output_width: 0,
+        output_height: 0,
+        viewport: GsnViewport { x: 0, y: 0, width: 0, height: 0 },
+        upload_stats: GsnUploadStats::default(),
+        upload_mode: GsnUploadMode::default(),
+        stream: None,
+        buffer: GsnSprite::new(0, 0),
     };
 
     gsn_renderer
 }
 
-#[derive(Clone, Copy, Debug)]
-pub struct Pixel {
-    r: f32,
-    g: f32,
-    b: f32,
-    a: f32,
-}
-
-pub fn pixel_rgb(r: u8, g: u8, b: u8) -> Pixel {
-    Pixel {
-        r: (r as f32 / u8::MAX as f32),
-        g: (g as f32 / u8::MAX as f32),
-        b: (b as f32 / u8::MAX as f32),
-        a: 1.0,
-    }
-}
-
-
-pub const WHITE: Pixel = Pixel {r: 1.0, g: 1.0, b: 1.0, a: 1.0};
-pub const BLACK: Pixel = Pixel {r: 0.0, g: 0.0, b: 0.0, a: 1.0};
-pub const RED: Pixel = Pixel {r: 1.0, g: 0.0, b: 0.0, a: 1.0};
-pub const GREEN: Pixel = Pixel {r: 0.0, g: 1.0, b: 0.0, a: 1.0};
-pub const BLUE: Pixel = Pixel {r: 0.0, g: 0.0, b: 1.0, a: 1.0};
-
-pub struct GsnSprite {
-    pub(crate) width: u32,
-    pub(crate) height: u32,
-    data: Vec<Pixel>,
-}
-
-impl GsnSprite {
-    pub fn set_pixel(&mut self, x: u32, y: u32, pixel: Pixel) -> bool {
-        if x < self.width.try_into().unwrap() && y < self.height.try_into().unwrap() {
-            let index: usize = y as usize * self.width as usize + x as usize;
-            self.data[index] = pixel;
-            true
-        }else{
-            false
-        }
+pub trait GsnBackend {
+    fn initialize(&mut self, width: u32, height: u32) -> Result<(), GsnError>;
+    fn render(&mut self);
+    fn buffer(&self) -> &GsnSprite;
+    fn buffer_mut(&mut self) -> &mut GsnSprite;
+    // Size of what's being drawn into and where in it the buffer goes. Nothing to do without a window.
+    fn set_viewport(&mut self, _output_width: u32, _output_height: u32, _viewport: GsnViewport) {}
+    // A fresh, cleared buffer of the new size, drawing with the old one's blend mode.
+    fn resize(&mut self, width: u32, height: u32) {
+        *self.buffer_mut() = self.buffer().resized(width, height);
     }
-    pub fn get_pixel(&self, x: u32, y: u32) -> Pixel {
-        let x = x.clamp(0,self.width);
-        let y = y.clamp(0, self.height);
-        let index: usize = y as usize * self.width as usize + x as usize;
-        self.data[index]
-
-    }
-    pub fn fill_rect(&mut self, x: u32, y: u32, w: u32, h: u32, p: Pixel) {
-        let x = x.clamp(0,self.width);
-        let y = y.clamp(0, self.height);
-        let x2 = (x + w).clamp( 0,self.width);
-        let y2 = (y + h).clamp(0,self.height);
-
-        for dx in x..x2 {
-            for dy in y..y2 {
-                self.set_pixel(dx,dy,p);
-            }
-        }
-    }
-    pub fn clear(&mut self, p: Pixel) {
-        self.fill_rect(0, 0, self.width, self.height, p);
+    fn set_clear_color(&mut self, _color: Pixel) {}
+    fn upload_stats(&self) -> GsnUploadStats {
+        GsnUploadStats::default()
     }
+    fn set_upload_mode(&mut self, _mode: GsnUploadMode) {}
 }
 
-impl GsnRenderer {
-    pub fn initialize(&mut self, width: u32, height: u32) {
+impl GsnBackend for GsnRenderer {
+    fn initialize(&mut self, width: u32, height: u32) -> Result<(), GsnError> {
         self.width = width;
         self.height = height;
-
-
-        self.buffer = GsnSprite {
-            width,
-            height,
-            data: Vec::with_capacity((width * height).try_into().unwrap()),
-        };
-
-        unsafe { self.buffer.data.set_len(self.buffer.data.capacity()); }
-        for p in self.buffer.data.iter_mut() {
-            *p = pixel_rgb(0, 0, 0);
+        self.buffer = GsnSprite::new(width, height);
+        self.set_viewport(width, height, GsnViewport { x: 0, y: 0, width, height });
+
+        // Every GL 3.3 entry point this uses, calling one that didn't load would abort.
+        let loaded = gl::GenVertexArrays::is_loaded() && gl::CreateShader::is_loaded() && gl::CreateProgram::is_loaded()
+            && gl::TexSubImage2D::is_loaded() && gl::MapBufferRange::is_loaded() && gl::FenceSync::is_loaded();
+        if !loaded {
+            return Err(GsnError::ContextInit("OpenGL 3.3 functions are missing".to_string()));
         }
 
-
         unsafe {
-            gl::ClearColor(
-                self.clear_color.r,
-                self.clear_color.g,
-                self.clear_color.b,
-                self.clear_color.a,
-            );
+            let [r, g, b, a] = self.clear_color.to_f32();
+            gl::ClearColor(r, g, b, a);
 
             gl::GenVertexArrays(1, &mut self.vao);
-            assert_ne!(self.vao, 0);
-            gl::BindVertexArray(self.vao);
-
             gl::GenBuffers(1, &mut self.vbo);
-            assert_ne!(0, self.vbo);
-            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
-
             gl::GenBuffers(1, &mut self.ebo);
-            assert_ne!(0, self.ebo);
+            if self.vao == 0 || self.vbo == 0 || self.ebo == 0 {
+                return Err(GsnError::ContextInit("could not create vertex buffers".to_string()));
+            }
+            gl::BindVertexArray(self.vao);
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
             gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.ebo);
             gl::BufferData(
                 gl::ELEMENT_ARRAY_BUFFER,
@@ -170,13 +237,16 @@ impl GsnRenderer {
                 gl::STATIC_DRAW,
             );
 
+            // Row 0 of the buffer is shown at the top of the window. Decoded images and text are stored top row
+            // first and cursor positions count down from the top, but texture rows go up from the bottom, so v
+            // runs top to bottom.
             type Vertex = [f32; 9];
             const ZOOM: f32 = 1.0;
             const VERTICES: [Vertex; 4] =
-                [[-ZOOM, -ZOOM, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0], // bottom left
-                    [ZOOM, -ZOOM, 0.0, 1.0, 0.0, 1.0, 1.0, 1.0, 1.0],  // bottom right
-                    [ZOOM, ZOOM, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0],   // top right
-                    [-ZOOM, ZOOM, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0]   // top left
+                [[-ZOOM, -ZOOM, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0], // bottom left
+                    [ZOOM, -ZOOM, 0.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0],  // bottom right
+                    [ZOOM, ZOOM, 0.0, 1.0, 0.0, 1.0, 1.0, 1.0, 1.0],   // top right
+                    [-ZOOM, ZOOM, 0.0, 0.0, 0.0, 1.0, 1.0, 1.0, 1.0]   // top left
                 ];
 
             const INDICES: [i32; 6] = [
@@ -222,87 +292,297 @@ impl GsnRenderer {
             );
 
             gl::GenTextures(1, &mut self.screen_buffer_texture);
-            gl::BindTexture(gl::TEXTURE_2D, self.screen_buffer_texture);
-            gl::TexImage2D(
-                gl::TEXTURE_2D,
-                0,
-                gl::RGBA.try_into().unwrap(),
-                self.buffer.width.try_into().unwrap(),
-                self.buffer.height.try_into().unwrap(),
-                0,
-                gl::RGBA,
-                gl::FLOAT,
-                self.buffer.data.as_ptr().cast(),
-            );
-            gl::GenerateMipmap(gl::TEXTURE_2D);
+            self.allocate_texture();
+            // Nearest sampling keeps scaled up pixels sharp.
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
 
             let vertex_shader = get_shader(
                 VERT_SHADER,
                 gl::VERTEX_SHADER,
-            );
+            )?;
 
             let fragment_shader = get_shader(
                 FRAG_SHADER,
                 gl::FRAGMENT_SHADER,
-            );
+            ).inspect_err(|_| gl::DeleteShader(vertex_shader))?;
 
-            self.shader_program = gl::CreateProgram();
-            gl::AttachShader(self.shader_program, vertex_shader);
-            gl::AttachShader(self.shader_program, fragment_shader);
-            gl::LinkProgram(self.shader_program);
+            let program = gl::CreateProgram();
+            gl::AttachShader(program, vertex_shader);
+            gl::AttachShader(program, fragment_shader);
+            gl::LinkProgram(program);
+            gl::DeleteShader(vertex_shader);
+            gl::DeleteShader(fragment_shader);
             let mut success = 0;
-            gl::GetProgramiv(self.shader_program, gl::LINK_STATUS, &mut success);
+            gl::GetProgramiv(program, gl::LINK_STATUS, &mut success);
             if success == 0 {
                 let mut v: Vec<u8> = Vec::with_capacity(1024);
                 let mut log_len = 0_i32;
                 gl::GetProgramInfoLog(
-                    self.shader_program,
+                    program,
                     1024,
                     &mut log_len,
                     v.as_mut_ptr().cast(),
                 );
-                v.set_len(log_len.try_into().unwrap());
-                panic!("Program Link Error: {}", String::from_utf8_lossy(&v));
+                v.set_len(log_len.max(0) as usize);
+                gl::DeleteProgram(program);
+                return Err(GsnError::ShaderLink(String::from_utf8_lossy(&v).into_owned()));
             }
+            self.shader_program = program;
+        }
+        Ok(())
+    }
 
-            gl::DeleteShader(vertex_shader);
-            gl::DeleteShader(fragment_shader);
+    fn render(&mut self) {
+        self.update_texture();
+        unsafe {
+            // Clear the whole window first, whatever the viewport leaves uncovered is the letterbox.
+            gl::Viewport(0, 0, self.output_width as i32, self.output_height as i32);
+            let [r, g, b, a] = self.clear_color.to_f32();
+            gl::ClearColor(r, g, b, a);
+            gl::Clear(gl::COLOR_BUFFER_BIT);
+            let v = self.viewport;
+            gl::Viewport(v.x, self.output_height as i32 - v.y - v.height as i32, v.width as i32, v.height as i32);
+            gl::UseProgram(self.shader_program);
+            gl::BindVertexArray(self.vao);
+            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.ebo);
+            gl::BindTexture(gl::TEXTURE_2D, self.screen_buffer_texture);
+            gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_INT, 0 as *const _);
         }
     }
-    fn update_texture(&mut self) {
+
+    fn buffer(&self) -> &GsnSprite {
+        &self.buffer
+    }
+
+    fn buffer_mut(&mut self) -> &mut GsnSprite {
+        &mut self.buffer
+    }
+
+    fn set_viewport(&mut self, output_width: u32, output_height: u32, viewport: GsnViewport) {
+        self.output_width = output_width;
+        self.output_height = output_height;
+        self.viewport = viewport;
+        if self.shader_program != 0 {
+            unsafe {
+                gl::Viewport(viewport.x, output_height as i32 - viewport.y - viewport.height as i32, viewport.width as i32, viewport.height as i32);
+            }
+        }
+    }
+
+    fn resize(&mut self, width: u32, height: u32) {
+        self.width = width;
+        self.height = height;
+        self.buffer = self.buffer.resized(width, height);
+        self.release_stream();
+        if self.screen_buffer_texture != 0 {
+            self.allocate_texture();
+        }
+    }
+
+    fn set_clear_color(&mut self, color: Pixel) {
+        self.clear_color = color;
+    }
+
+    fn upload_stats(&self) -> GsnUploadStats {
+        self.upload_stats
+    }
+
+    fn set_upload_mode(&mut self, mode: GsnUploadMode) {
+        self.upload_mode = mode;
+        if mode == GsnUploadMode::Direct {
+            self.release_stream();
+        }
+    }
+}
+
+impl GsnRenderer {
+    // Storage only, the buffer starts fully dirty so the first render fills it.
+    fn allocate_texture(&mut self) {
         unsafe {
             gl::BindTexture(gl::TEXTURE_2D, self.screen_buffer_texture);
             gl::TexImage2D(
                 gl::TEXTURE_2D,
                 0,
-                gl::RGBA.try_into().unwrap(),
+                gl::RGBA8.try_into().unwrap(),
                 self.buffer.width.try_into().unwrap(),
                 self.buffer.height.try_into().unwrap(),
                 0,
                 gl::RGBA,
-                gl::FLOAT,
-                self.buffer.data.as_ptr().cast(),
+                gl::UNSIGNED_BYTE,
+                std::ptr::null(),
             );
         }
     }
 
-    pub fn render(&mut self) {
-        self.update_texture();
+    fn update_texture(&mut self) {
+        let rects = plan_upload(&mut self.buffer, &mut self.upload_stats);
+        if rects.is_empty() {
+            return;
+        }
         unsafe {
-            gl::Clear(gl::COLOR_BUFFER_BIT);
-            gl::UseProgram(self.shader_program);
-            gl::BindVertexArray(self.vao);
-            gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.ebo);
             gl::BindTexture(gl::TEXTURE_2D, self.screen_buffer_texture);
-            gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_INT, 0 as *const _);
+            // Rows of a sub rectangle are still a whole buffer row apart.
+            gl::PixelStorei(gl::UNPACK_ROW_LENGTH, self.buffer.width as i32);
+            let streamed = self.upload_mode == GsnUploadMode::Streaming && self.stream_rects(&rects);
+            if !streamed {
+                for r in rects {
+                    let start = r.y as usize * self.buffer.width as usize + r.x as usize;
+                    gl::TexSubImage2D(
+                        gl::TEXTURE_2D,
+                        0,
+                        r.x,
+                        r.y,
+                        r.w as i32,
+                        r.h as i32,
+                        gl::RGBA,
+                        gl::UNSIGNED_BYTE,
+                        self.buffer.data[start..].as_ptr().cast(),
+                    );
+                }
+            }
+            gl::PixelStorei(gl::UNPACK_ROW_LENGTH, 0);
+        }
+    }
+
+    // Copies the rectangles into the next pixel buffer of the ring and uploads from there. False if the
+    // buffer couldn't be mapped, the caller then uploads directly.
+    unsafe fn stream_rects(&mut self, rects: &[GsnRect]) -> bool {
+        let size = self.buffer.as_bytes().len();
+        if self.stream.as_ref().is_none_or(|s| s.size != size) {
+            self.release_stream();
+            self.stream = Some(GsnStream::new(size));
+        }
+        let stream = self.stream.as_mut().unwrap();
+        let index = stream.next;
+        stream.next = (index + 1) % STREAM_BUFFERS;
+
+        // Only waits if the GPU is still reading what was written here STREAM_BUFFERS frames ago.
+        let fence = std::mem::replace(&mut stream.fences[index], std::ptr::null());
+        if !fence.is_null() {
+            gl::ClientWaitSync(fence, gl::SYNC_FLUSH_COMMANDS_BIT, gl::TIMEOUT_IGNORED);
+            gl::DeleteSync(fence);
+        }
+
+        gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, stream.buffers[index]);
+        let persistent = !stream.mapped[index].is_null();
+        let target = if persistent {
+            stream.mapped[index]
+        } else {
+            gl::MapBufferRange(gl::PIXEL_UNPACK_BUFFER, 0, size as isize, gl::MAP_WRITE_BIT | gl::MAP_INVALIDATE_BUFFER_BIT).cast::<u8>()
+        };
+        if target.is_null() {
+            gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
+            return false;
+        }
+
+        let bytes = self.buffer.as_bytes();
+        let stride = self.buffer.width as usize * size_of::<Pixel>();
+        for r in rects {
+            let row_len = r.w as usize * size_of::<Pixel>();
+            for y in r.y as usize..r.y as usize + r.h as usize {
+                let offset = y * stride + r.x as usize * size_of::<Pixel>();
+                std::ptr::copy_nonoverlapping(bytes[offset..offset + row_len].as_ptr(), target.add(offset), row_len);
+            }
+        }
+        if !persistent {
+            gl::UnmapBuffer(gl::PIXEL_UNPACK_BUFFER);
+        }
+
+        // With a pixel buffer bound the data pointer is an offset into it.
+        for r in rects {
+            let offset = r.y as usize * stride + r.x as usize * size_of::<Pixel>();
+            gl::TexSubImage2D(
+                gl::TEXTURE_2D,
+                0,
+                r.x,
+                r.y,
+                r.w as i32,
+                r.h as i32,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                offset as *const _,
+            );
+        }
+        stream.fences[index] = gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0);
+        gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
+        true
+    }
+
+    fn release_stream(&mut self) {
+        if let Some(stream) = self.stream.take() {
+            unsafe {
+                for fence in stream.fences.into_iter().filter(|f| !f.is_null()) {
+                    gl::DeleteSync(fence);
+                }
+                // Deleting a buffer unmaps it, and the driver holds on to it until pending uploads are done.
+                gl::DeleteBuffers(STREAM_BUFFERS as i32, stream.buffers.as_ptr());
+            }
         }
     }
 }
 
-pub fn get_shader(src: &str, shader_type: gl::types::GLenum) -> u32 {
+impl GsnStream {
+    // Persistently mapped when the context has buffer storage. Otherwise, or if the persistent map fails, each
+    // upload maps its buffer for the one write, and if that fails too stream_rects says so and the caller
+    // uploads straight from the sprite.
+    unsafe fn new(size: usize) -> GsnStream {
+        let mut stream = GsnStream {
+            buffers: [0; STREAM_BUFFERS],
+            mapped: [std::ptr::null_mut(); STREAM_BUFFERS],
+            fences: [std::ptr::null(); STREAM_BUFFERS],
+            next: 0,
+            size
+        };
+        gl::GenBuffers(STREAM_BUFFERS as i32, stream.buffers.as_mut_ptr());
+        let persistent = has_buffer_storage();
+        for (buffer, mapped) in stream.buffers.iter().zip(stream.mapped.iter_mut()) {
+            gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, *buffer);
+            if persistent {
+                // Coherent, so writes through the pointer are seen by the next upload without a flush.
+                let flags = gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT | gl::MAP_COHERENT_BIT;
+                gl::BufferStorage(gl::PIXEL_UNPACK_BUFFER, size as isize, std::ptr::null(), flags);
+                *mapped = gl::MapBufferRange(gl::PIXEL_UNPACK_BUFFER, 0, size as isize, flags).cast();
+            } else {
+                gl::BufferData(gl::PIXEL_UNPACK_BUFFER, size as isize, std::ptr::null(), gl::STREAM_DRAW);
+            }
+        }
+        gl::BindBuffer(gl::PIXEL_UNPACK_BUFFER, 0);
+        stream
+    }
+}
+
+// Core since GL 4.4, before that only with GL_ARB_buffer_storage. The entry point alone says nothing, glx
+// hands out addresses for any name whether the context supports it or not.
+unsafe fn has_buffer_storage() -> bool {
+    if !gl::BufferStorage::is_loaded() {
+        return false;
+    }
+    let (mut major, mut minor) = (0, 0);
+    gl::GetIntegerv(gl::MAJOR_VERSION, &mut major);
+    gl::GetIntegerv(gl::MINOR_VERSION, &mut minor);
+    if (major, minor) >= (4, 4) {
+        return true;
+    }
+    if !gl::GetStringi::is_loaded() {
+        return false;
+    }
+    let mut count = 0;
+    gl::GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+    (0..count.max(0) as u32).any(|i| {
+        let name = gl::GetStringi(gl::EXTENSIONS, i);
+        !name.is_null() && std::ffi::CStr::from_ptr(name.cast()).to_bytes() == b"GL_ARB_buffer_storage"
+    })
+}
+
+pub fn get_shader(src: &str, shader_type: gl::types::GLenum) -> Result<u32, GsnError> {
     let shader_id = unsafe {
         let shader_id = gl::CreateShader(shader_type);
-        assert_ne!(shader_id, 0);
+        if shader_id == 0 {
+            return Err(GsnError::ContextInit("could not create a shader".to_string()));
+        }
 
         gl::ShaderSource(
             shader_id,
@@ -324,10 +604,11 @@ pub fn get_shader(src: &str, shader_type: gl::types::GLenum) -> u32 {
                 &mut log_len,
                 v.as_mut_ptr().cast(),
             );
-            v.set_len(log_len.try_into().unwrap());
-            panic!("Shader Compile Error: {}", String::from_utf8_lossy(&v));
+            v.set_len(log_len.max(0) as usize);
+            gl::DeleteShader(shader_id);
+            return Err(GsnError::ShaderCompile(String::from_utf8_lossy(&v).into_owned()));
         }
         shader_id
     };
-    return shader_id;
+    Ok(shader_id)
 }
\ No newline at end of file
@@ -0,0 +1,194 @@
+use crate::blend::GsnBlendMode;
+use crate::error::GsnError;
+use crate::blit::GsnRect;
+use std::mem::size_of;
+
+// 8 bit RGBA, laid out exactly as GL's RGBA/UNSIGNED_BYTE so a sprite's data uploads as is.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pixel {
+    pub(crate) r: u8,
+    pub(crate) g: u8,
+    pub(crate) b: u8,
+    pub(crate) a: u8,
+}
+
+pub const fn pixel_rgb(r: u8, g: u8, b: u8) -> Pixel {
+    Pixel { r, g, b, a: u8::MAX }
+}
+
+pub const fn pixel_rgba(r: u8, g: u8, b: u8, a: u8) -> Pixel {
+    Pixel { r, g, b, a }
+}
+
+impl Pixel {
+    pub fn r(&self) -> u8 {
+        self.r
+    }
+    pub fn g(&self) -> u8 {
+        self.g
+    }
+    pub fn b(&self) -> u8 {
+        self.b
+    }
+    pub fn a(&self) -> u8 {
+        self.a
+    }
+    // Channels from 0 to 1, clamped and rounded to the nearest 8 bit step.
+    pub fn from_f32(r: f32, g: f32, b: f32, a: f32) -> Pixel {
+        Pixel { r: to_u8(r), g: to_u8(g), b: to_u8(b), a: to_u8(a) }
+    }
+    pub fn to_f32(&self) -> [f32; 4] {
+        const SCALE: f32 = 1.0 / u8::MAX as f32;
+        [self.r, self.g, self.b, self.a].map(|c| c as f32 * SCALE)
+    }
+}
+
+fn to_u8(c: f32) -> u8 {
+    // Never negative after the clamp, so adding a half and truncating rounds. round() is a libm call.
+    (c.clamp(0.0, 1.0) * u8::MAX as f32 + 0.5) as u8
+}
+
+
+pub const WHITE: Pixel = pixel_rgb(255, 255, 255);
+pub const BLACK: Pixel = pixel_rgb(0, 0, 0);
+pub const RED: Pixel = pixel_rgb(255, 0, 0);
+pub const GREEN: Pixel = pixel_rgb(0, 255, 0);
+pub const BLUE: Pixel = pixel_rgb(0, 0, 255);
+
+// Changed regions are tracked as a few rectangles, past this many they merge into their bounding box.
+const MAX_DIRTY_RECTS: usize = 8;
+
+// Pixels are stored row by row with (0, 0) at the top left, x growing right and y growing down.
+// Decoders, text and the drawing functions all use that order, and the engine shows the buffer
+// the same way up, so row 0 is the top of the window.
+#[derive(Clone)]
+pub struct GsnSprite {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) data: Vec<Pixel>,
+    blend: GsnBlendMode,
+    dirty: Vec<GsnRect>,
+}
+
+impl GsnSprite {
+    pub fn new(width: u32, height: u32) -> GsnSprite {
+        GsnSprite {
+            width,
+            height,
+            data: vec![BLACK; width as usize * height as usize],
+            blend: GsnBlendMode::Normal,
+            dirty: vec![GsnRect::new(0, 0, width, height)],
+        }
+    }
+    // Tightly packed 8 bit RGBA rows, top row first.
+    pub fn from_rgba8(width: u32, height: u32, rgba: &[u8]) -> Result<GsnSprite, GsnError> {
+        if rgba.len() != width as usize * height as usize * 4 {
+            return Err(GsnError::ImageDecode(format!("expected {} bytes of RGBA for {}x{}, got {}", width as usize * height as usize * 4, width, height, rgba.len())));
+        }
+        let mut sprite = GsnSprite::new(width, height);
+        for (p, c) in sprite.data.iter_mut().zip(rgba.chunks_exact(4)) {
+            *p = pixel_rgba(c[0], c[1], c[2], c[3]);
+        }
+        Ok(sprite)
+    }
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        self.as_bytes().to_vec()
+    }
+    // The pixels as tightly packed 8 bit RGBA rows, without copying.
+    pub fn as_bytes(&self) -> &[u8] {
+        // Pixel is repr(C) with four u8 fields, so it has no padding and an alignment of 1.
+        unsafe { std::slice::from_raw_parts(self.data.as_ptr().cast(), self.data.len() * size_of::<Pixel>()) }
+    }
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+    pub fn blend_mode(&self) -> &GsnBlendMode {
+        &self.blend
+    }
+    pub fn set_blend_mode(&mut self, mode: GsnBlendMode) {
+        self.blend = mode;
+    }
+    pub fn set_pixel(&mut self, x: u32, y: u32, pixel: Pixel) -> bool {
+        if x < self.width && y < self.height {
+            let index: usize = y as usize * self.width as usize + x as usize;
+            if let Some(p) = self.blend.apply(pixel, self.data[index]) {
+                self.data[index] = p;
+                self.mark_dirty(x as i32, y as i32);
+            }
+            true
+        }else{
+            false
+        }
+    }
+    // Signed version of set_pixel, anything outside the sprite is silently clipped.
+    pub fn draw_pixel(&mut self, x: i32, y: i32, pixel: Pixel) -> bool {
+        if x < 0 || y < 0 {
+            return false;
+        }
+        self.set_pixel(x as u32, y as u32, pixel)
+    }
+    // None outside the sprite.
+    pub fn get_pixel(&self, x: u32, y: u32) -> Option<Pixel> {
+        if x >= self.width || y >= self.height {
+            return None;
+        }
+        Some(self.data[y as usize * self.width as usize + x as usize])
+    }
+    pub fn fill_rect(&mut self, x: u32, y: u32, w: u32, h: u32, p: Pixel) {
+        let x = x.clamp(0,self.width);
+        let y = y.clamp(0, self.height);
+        let x2 = (x + w).clamp( 0,self.width);
+        let y2 = (y + h).clamp(0,self.height);
+
+        for dy in y..y2 {
+            for dx in x..x2 {
+                self.set_pixel(dx,dy,p);
+            }
+        }
+    }
+    // Always overwrites, regardless of the blend mode.
+    pub fn clear(&mut self, p: Pixel) {
+        self.data.fill(p);
+        self.mark_all_dirty();
+    }
+    // Regions written since the backend last uploaded this sprite, they may overlap.
+    pub fn dirty_rects(&self) -> &[GsnRect] {
+        &self.dirty
+    }
+    pub fn mark_all_dirty(&mut self) {
+        self.dirty = vec![GsnRect::new(0, 0, self.width, self.height)];
+    }
+    // A cleared sprite of another size that still draws with this one's blend mode, for buffers
+    // that get replaced on resize.
+    pub(crate) fn resized(&self, width: u32, height: u32) -> GsnSprite {
+        let mut sprite = GsnSprite::new(width, height);
+        sprite.blend = self.blend.clone();
+        sprite
+    }
+    pub(crate) fn take_dirty(&mut self) -> Vec<GsnRect> {
+        std::mem::take(&mut self.dirty)
+    }
+    // Grows a rectangle the pixel touches, most writes land next to the previous one.
+    fn mark_dirty(&mut self, x: i32, y: i32) {
+        if let Some(r) = self.dirty.last() {
+            if x >= r.x && x < r.x + r.w as i32 && y >= r.y && y < r.y + r.h as i32 {
+                return;
+            }
+        }
+        let pixel = GsnRect::new(x, y, 1, 1);
+        let touching = self.dirty.iter()
+            .rposition(|r| x >= r.x - 1 && x <= r.x + r.w as i32 && y >= r.y - 1 && y <= r.y + r.h as i32);
+        match touching {
+            Some(i) => self.dirty[i] = self.dirty[i].union(pixel),
+            None if self.dirty.len() < MAX_DIRTY_RECTS => self.dirty.push(pixel),
+            None => {
+                let bounds = self.dirty.iter().fold(pixel, |acc, r| acc.union(*r));
+                self.dirty = vec![bounds];
+            }
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::VecDeque;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Frames averaged over for the stats.
+const STATS_WINDOW: usize = 120;
+// A frame longer than this is treated as this long, so a stall doesn't queue up hundreds of fixed steps.
+const MAX_FRAME_TIME: f64 = 0.25;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GsnFrameStats {
+    pub fps: f64,
+    // Seconds, over the last STATS_WINDOW frames.
+    pub frame_time: f64,
+    pub min_frame_time: f64,
+    pub max_frame_time: f64,
+    pub frames: u64
+}
+
+// Frame timing. tick() once a frame gives dt; with a fixed step set, `while clock.step() { .. }` runs the
+// simulation at that rate and alpha() says how far between the last two steps to draw.
+#[derive(Debug, Clone)]
+pub struct GsnClock {
+    last: Option<Instant>,
+    dt: f64,
+    fixed_step: Option<f64>,
+    accumulator: f64,
+    frame_cap: Option<f64>,
+    history: VecDeque<f64>,
+    frames: u64
+}
+
+impl Default for GsnClock {
+    fn default() -> GsnClock {
+        GsnClock::new()
+    }
+}
+
+impl GsnClock {
+    pub fn new() -> GsnClock {
+        GsnClock {
+            last: None,
+            dt: 0.0,
+            fixed_step: None,
+            accumulator: 0.0,
+            frame_cap: None,
+            history: VecDeque::with_capacity(STATS_WINDOW),
+            frames: 0
+        }
+    }
+
+    // Seconds per simulation step, e.g. 1.0 / 60.0. None turns fixed stepping off.
+    pub fn set_fixed_step(&mut self, step: Option<f64>) {
+        self.fixed_step = step.filter(|s| *s > 0.0);
+        self.accumulator = 0.0;
+    }
+
+    pub fn fixed_step(&self) -> Option<f64> {
+        self.fixed_step
+    }
+
+    // Frames per second to hold tick() to, None runs as fast as the loop allows (or vsync does).
+    pub fn set_frame_cap(&mut self, fps: Option<f64>) {
+        self.frame_cap = fps.filter(|f| *f > 0.0);
+    }
+
+    pub fn frame_cap(&self) -> Option<f64> {
+        self.frame_cap
+    }
+
+    // Waits out the frame cap, then measures the time since the previous tick. The first tick has a dt of 0.
+    pub fn tick(&mut self) -> f64 {
+        // A cap so low its frame time doesn't fit in a Duration, or past the last tick in an Instant, is no cap.
+        let target = self.frame_cap.zip(self.last)
+            .and_then(|(fps, last)| last.checked_add(Duration::try_from_secs_f64(1.0 / fps).ok()?));
+        if let Some(target) = target {
+            // Sleep most of the way, the scheduler overshoots, and spin the rest.
+            loop {
+                let now = Instant::now();
+                if now >= target {
+                    break;
+                }
+                let remaining = target - now;
+                if remaining > Duration::from_millis(2) {
+                    thread::sleep(remaining - Duration::from_millis(1));
+                } else {
+                    std::hint::spin_loop();
+                }
+            }
+        }
+        let now = Instant::now();
+        let dt = self.last.map_or(0.0, |last| (now - last).as_secs_f64());
+        self.last = Some(now);
+        self.advance(dt)
+    }
+
+    // tick() with a given dt instead of the wall clock, for replays, tests and headless runs.
+    pub fn advance(&mut self, dt: f64) -> f64 {
+        self.dt = dt.max(0.0);
+        self.frames += 1;
+        if self.fixed_step.is_some() {
+            self.accumulator += self.dt.min(MAX_FRAME_TIME);
+        }
+        if self.history.len() == STATS_WINDOW {
+            self.history.pop_front();
+        }
+        self.history.push_back(self.dt);
+        self.dt
+    }
+
+    pub fn dt(&self) -> f64 {
+        self.dt
+    }
+
+    // True while a whole fixed step is banked, taking it out. Always false without a fixed step.
+    pub fn step(&mut self) -> bool {
+        match self.fixed_step {
+            Some(step) if self.accumulator >= step => {
+                self.accumulator -= step;
+                true
+            }
+            _ => false
+        }
+    }
+
+    // How far into the next fixed step the frame is, from 0 to 1.
+    pub fn alpha(&self) -> f64 {
+        self.fixed_step.map_or(1.0, |step| (self.accumulator / step).clamp(0.0, 1.0))
+    }
+
+    pub fn stats(&self) -> GsnFrameStats {
+        let count = self.history.len().max(1) as f64;
+        let frame_time = self.history.iter().sum::<f64>() / count;
+        GsnFrameStats {
+            fps: if frame_time > 0.0 { 1.0 / frame_time } else { 0.0 },
+            frame_time,
+            min_frame_time: self.history.iter().copied().reduce(f64::min).unwrap_or(0.0),
+            max_frame_time: self.history.iter().copied().reduce(f64::max).unwrap_or(0.0),
+            frames: self.frames
+        }
+    }
+}
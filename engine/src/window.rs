@@ -0,0 +1,127 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::mpsc::Receiver;
+use glfw::{Context, Glfw, SwapInterval, WindowHint};
+use crate::error::GsnError;
+use crate::GsnWindowMode;
+
+pub struct GsnWindow {
+    pub(crate) glfw: Glfw,
+    pub window: glfw::Window,
+    pub events: Receiver<(f64,glfw::WindowEvent)>,
+    mode: GsnWindowMode,
+    // Position and size to go back to when leaving fullscreen.
+    windowed: (i32, i32, u32, u32)
+}
+
+#[derive(Debug, Clone)]
+pub struct GsnWindowSettings {
+    pub title: String,
+    pub width: u32,
+    pub height: u32,
+    pub mode: GsnWindowMode,
+    pub resizable: bool,
+    pub vsync: bool
+}
+
+// glfw says why a call failed through its error callback, this keeps the message for the GsnError.
+fn keep_error(_: glfw::Error, description: String, last: &Rc<RefCell<Option<String>>>) {
+    *last.borrow_mut() = Some(description);
+}
+
+pub(crate) fn create_window(settings: &GsnWindowSettings) -> Result<GsnWindow, GsnError> {
+    let last_error = Rc::new(RefCell::new(None));
+    let callback = glfw::Callback { f: keep_error as fn(_, _, &_), data: last_error.clone() };
+    let describe = |fallback: String| last_error.borrow_mut().take().unwrap_or(fallback);
+    let mut glfw = glfw::init(Some(callback)).map_err(|e| GsnError::ContextInit(describe(e.to_string())))?;
+    glfw.window_hint(WindowHint::Resizable(settings.resizable));
+    // Created hidden and windowed, then moved into its mode, so there's one path for switching and no flash.
+    glfw.window_hint(WindowHint::Visible(false));
+    let (mut window, events) = glfw.create_window(
+        settings.width,
+        settings.height,
+        &settings.title,
+        glfw::WindowMode::Windowed)
+        .ok_or_else(|| GsnError::WindowCreation(describe(format!("no {}x{} window", settings.width, settings.height))))?;
+    // Later errors have no caller to go back to, they're printed instead.
+    glfw.set_error_callback(glfw::LOG_ERRORS);
+    window.make_current();
+    glfw.set_swap_interval(if settings.vsync { SwapInterval::Sync(1) } else { SwapInterval::None });
+    window.set_key_polling(true);
+    // Without this glfw never reports caps lock and num lock in the modifier bits.
+    window.set_store_lock_key_mods(true);
+    window.set_char_polling(true);
+    window.set_mouse_button_polling(true);
+    window.set_cursor_pos_polling(true);
+    window.set_scroll_polling(true);
+    window.set_focus_polling(true);
+    window.set_framebuffer_size_polling(true);
+    window.set_close_polling(true);
+    window.set_drag_and_drop_polling(true);
+
+    let (x, y) = window.get_pos();
+    let mut window = GsnWindow {
+        glfw,
+        window,
+        events,
+        mode: GsnWindowMode::WINDOWED,
+        windowed: (x, y, settings.width, settings.height)
+    };
+    window.set_mode(settings.mode);
+    window.window.show();
+    Ok(window)
+}
+
+impl GsnWindow {
+    pub(crate) fn poll_events(&mut self) -> Vec<glfw::WindowEvent> {
+        self.glfw.poll_events();
+        glfw::flush_messages(&self.events).map(|(_, event)| event).collect()
+    }
+
+    pub(crate) fn swap_buffers(&mut self) {
+        self.window.swap_buffers();
+    }
+
+    pub fn mode(&self) -> GsnWindowMode {
+        self.mode
+    }
+
+    // Fullscreen takes the monitor's current video mode, a monitor index that doesn't exist means the primary.
+    pub(crate) fn set_mode(&mut self, mode: GsnWindowMode) {
+        if self.mode == GsnWindowMode::WINDOWED {
+            let (x, y) = self.window.get_pos();
+            let (w, h) = self.window.get_size();
+            self.windowed = (x, y, w.max(1) as u32, h.max(1) as u32);
+        }
+        let GsnWindow { glfw, window, windowed, .. } = self;
+        match mode {
+            GsnWindowMode::WINDOWED => {
+                let (x, y, w, h) = *windowed;
+                window.set_decorated(true);
+                window.set_monitor(glfw::WindowMode::Windowed, x, y, w, h, None);
+            }
+            GsnWindowMode::FULLSCREEN(monitor) | GsnWindowMode::BORDERLESS(monitor) => {
+                glfw.with_connected_monitors(|_, monitors| {
+                    let Some(monitor) = monitors.get(monitor).or(monitors.first()) else { return };
+                    let Some(video) = monitor.get_video_mode() else { return };
+                    if let GsnWindowMode::FULLSCREEN(_) = mode {
+                        window.set_monitor(glfw::WindowMode::FullScreen(monitor), 0, 0, video.width, video.height, Some(video.refresh_rate));
+                    } else {
+                        let (x, y) = monitor.get_pos();
+                        window.set_decorated(false);
+                        window.set_monitor(glfw::WindowMode::Windowed, x, y, video.width, video.height, None);
+                    }
+                });
+            }
+        }
+        self.mode = mode;
+    }
+
+    pub(crate) fn set_vsync(&mut self, vsync: bool) {
+        self.glfw.set_swap_interval(if vsync { SwapInterval::Sync(1) } else { SwapInterval::None });
+    }
+
+    pub(crate) fn set_resizable(&mut self, resizable: bool) {
+        self.window.set_resizable(resizable);
+    }
+}
@@ -0,0 +1,70 @@
+use engine::app::GsnApp;
+use engine::{GsnAction, GsnEngine, GsnEvent, GsnKey, GsnModifiers};
+
+#[derive(Default)]
+struct Recorder {
+    log: Vec<String>,
+    updates: u32
+}
+
+impl GsnApp for Recorder {
+    fn on_create(&mut self, engine: &mut GsnEngine) {
+        self.log.push("create".to_string());
+        engine.push_event(GsnEvent::KeyPress(GsnKey::A, GsnAction::Press, GsnModifiers::default()));
+        engine.push_event(GsnEvent::KeyPress(GsnKey::B, GsnAction::Press, GsnModifiers::default()));
+    }
+
+    fn on_update(&mut self, engine: &mut GsnEngine, _dt: f64) {
+        self.updates += 1;
+        self.log.push("update".to_string());
+        if self.updates == 3 {
+            engine.exit();
+        }
+    }
+
+    fn on_event(&mut self, _engine: &mut GsnEngine, event: &GsnEvent) {
+        match event {
+            GsnEvent::Init => self.log.push("init".to_string()),
+            GsnEvent::Draw => self.log.push("draw".to_string()),
+            GsnEvent::KeyPress(key, _, _) => self.log.push(format!("{:?}", key)),
+            _ => {}
+        }
+    }
+
+    fn on_destroy(&mut self, _engine: &mut GsnEngine) {
+        self.log.push("destroy".to_string());
+    }
+}
+
+#[test]
+fn events_arrive_in_order_and_exit_ends_the_loop() {
+    let mut engine = GsnEngine::headless(8, 8);
+    let mut app = Recorder::default();
+    engine.run(&mut app);
+    assert_eq!(app.log, ["create", "init", "A", "B", "update", "update", "update", "destroy"]);
+}
+
+#[test]
+fn update_queues_one_draw_and_render_none() {
+    let mut engine = GsnEngine::headless(8, 8);
+    engine.actions.clear();
+    engine.render();
+    assert!(engine.actions.is_empty());
+    engine.update();
+    engine.update();
+    let draws = engine.actions.iter().filter(|e| matches!(e, GsnEvent::Draw)).count();
+    assert_eq!(draws, 2);
+}
+
+#[test]
+fn run_loop_stops_when_the_frame_returns_false() {
+    let mut engine = GsnEngine::headless(8, 8);
+    let mut frames = 0;
+    engine.run_loop(|engine, _dt| {
+        frames += 1;
+        assert_eq!(engine.frames(), frames - 1);
+        frames < 3
+    });
+    assert_eq!((frames, engine.frames()), (3, 2));
+    assert!(engine.should_close() && engine.actions.is_empty());
+}
@@ -0,0 +1,54 @@
+use engine::blend::GsnBlendMode;
+use engine::renderer::{pixel_rgba, GsnSprite, Pixel};
+
+const SRC: Pixel = pixel_rgba(200, 100, 50, 128);
+const DST: Pixel = pixel_rgba(40, 80, 120, 255);
+
+fn blend(mode: GsnBlendMode, src: Pixel, dst: Pixel) -> Pixel {
+    let mut s = GsnSprite::new(1, 1);
+    s.set_pixel(0, 0, dst);
+    s.set_blend_mode(mode);
+    s.set_pixel(0, 0, src);
+    s.get_pixel(0, 0).unwrap()
+}
+
+#[test]
+fn normal() {
+    assert_eq!(blend(GsnBlendMode::Normal, SRC, DST), SRC);
+}
+
+#[test]
+fn mask() {
+    assert_eq!(blend(GsnBlendMode::Mask, SRC, DST), DST);
+    assert_eq!(blend(GsnBlendMode::Mask, pixel_rgba(1, 2, 3, 255), DST), pixel_rgba(1, 2, 3, 255));
+}
+
+#[test]
+fn alpha() {
+    assert_eq!(blend(GsnBlendMode::Alpha, SRC, DST), pixel_rgba(120, 90, 85, 255));
+    // Over nothing the source comes through as it is, nothing over nothing stays transparent black.
+    assert_eq!(blend(GsnBlendMode::Alpha, SRC, pixel_rgba(9, 9, 9, 0)), SRC);
+    assert_eq!(blend(GsnBlendMode::Alpha, pixel_rgba(5, 5, 5, 0), pixel_rgba(9, 9, 9, 0)), pixel_rgba(0, 0, 0, 0));
+}
+
+#[test]
+fn additive() {
+    assert_eq!(blend(GsnBlendMode::Additive, SRC, DST), pixel_rgba(140, 130, 145, 255));
+    assert_eq!(blend(GsnBlendMode::Additive, pixel_rgba(255, 255, 255, 255), DST), pixel_rgba(255, 255, 255, 255));
+}
+
+#[test]
+fn multiply() {
+    assert_eq!(blend(GsnBlendMode::Multiply, SRC, DST), pixel_rgba(36, 56, 72, 255));
+}
+
+#[test]
+fn screen() {
+    assert_eq!(blend(GsnBlendMode::Screen, SRC, DST), pixel_rgba(125, 114, 133, 255));
+}
+
+#[test]
+fn custom() {
+    let swap = GsnBlendMode::custom(|src: Pixel, dst: Pixel| pixel_rgba(dst.b(), src.g(), dst.r(), 77));
+    assert_eq!(blend(swap, SRC, DST), pixel_rgba(120, 100, 40, 77));
+}
@@ -0,0 +1,61 @@
+use engine::blit::{GsnBlit, GsnColorKey, GsnRect};
+use engine::renderer::{pixel_rgb, pixel_rgba, GsnSprite, Pixel, BLACK};
+
+// A 3x2 source with a distinct color per pixel, red channel x, green channel y.
+fn source() -> GsnSprite {
+    let mut s = GsnSprite::new(3, 2);
+    for y in 0..2 {
+        for x in 0..3 {
+            s.set_pixel(x, y, pixel_rgb(x as u8 + 1, y as u8 + 1, 0));
+        }
+    }
+    s
+}
+
+fn rows(s: &GsnSprite) -> Vec<Vec<Pixel>> {
+    (0..s.height()).map(|y| (0..s.width()).map(|x| s.get_pixel(x, y).unwrap()).collect()).collect()
+}
+
+fn p(x: u8, y: u8) -> Pixel {
+    pixel_rgb(x, y, 0)
+}
+
+const O: Pixel = BLACK;
+
+#[test]
+fn whole_and_flipped() {
+    let mut dst = GsnSprite::new(4, 3);
+    dst.draw_sprite(&source(), 1, 1);
+    assert_eq!(rows(&dst), [vec![O, O, O, O], vec![O, p(1, 1), p(2, 1), p(3, 1)], vec![O, p(1, 2), p(2, 2), p(3, 2)]]);
+
+    let mut dst = GsnSprite::new(3, 2);
+    dst.draw_sprite_ex(&source(), 0, 0, GsnBlit { flip_x: true, flip_y: true, ..GsnBlit::default() });
+    assert_eq!(rows(&dst), [vec![p(3, 2), p(2, 2), p(1, 2)], vec![p(3, 1), p(2, 1), p(1, 1)]]);
+}
+
+#[test]
+fn partial_and_clipped() {
+    // Source rect hanging off the source's right edge, placed hanging off the destination's top left.
+    let mut dst = GsnSprite::new(3, 2);
+    dst.draw_partial_sprite(&source(), GsnRect::new(1, 0, 4, 2), -1, 0);
+    assert_eq!(rows(&dst), [vec![p(3, 1), O, O], vec![p(3, 2), O, O]]);
+
+    let mut dst = GsnSprite::new(2, 2);
+    dst.draw_sprite(&source(), -2, 1);
+    assert_eq!(rows(&dst), [vec![O, O], vec![p(3, 1), O]]);
+    dst.draw_sprite(&source(), 100, -100);
+    assert_eq!(rows(&dst), [vec![O, O], vec![p(3, 1), O]]);
+}
+
+#[test]
+fn color_keys() {
+    let mut src = source();
+    src.set_pixel(1, 0, pixel_rgba(9, 9, 9, 10));
+    let mut dst = GsnSprite::new(3, 1);
+    dst.draw_sprite_ex(&src, 0, 0, GsnBlit { key: GsnColorKey::Color(p(1, 1)), ..GsnBlit::default() });
+    assert_eq!(rows(&dst), [vec![O, pixel_rgba(9, 9, 9, 10), p(3, 1)]]);
+
+    let mut dst = GsnSprite::new(3, 1);
+    dst.draw_sprite_ex(&src, 0, 0, GsnBlit { key: GsnColorKey::Alpha(128), ..GsnBlit::default() });
+    assert_eq!(rows(&dst), [vec![p(1, 1), O, p(3, 1)]]);
+}
@@ -0,0 +1,163 @@
+use engine::renderer::{GsnSprite, BLACK, WHITE};
+
+// Rows of the sprite, '#' for anything drawn.
+fn grid(s: &GsnSprite) -> Vec<String> {
+    (0..s.height()).map(|y| (0..s.width()).map(|x| if s.get_pixel(x, y) == Some(BLACK) { '.' } else { '#' }).collect()).collect()
+}
+
+fn line(x1: i32, y1: i32) -> Vec<String> {
+    let mut s = GsnSprite::new(7, 7);
+    s.draw_line(3, 3, x1, y1, WHITE);
+    grid(&s)
+}
+
+#[test]
+fn lines_in_every_octant() {
+    assert_eq!(line(6, 2), [".......", ".......", ".....##", "...##..", ".......", ".......", "......."]);
+    assert_eq!(line(6, 4), [".......", ".......", ".......", "...##..", ".....##", ".......", "......."]);
+    assert_eq!(line(4, 6), [".......", ".......", ".......", "...#...", "...#...", "....#..", "....#.."]);
+    assert_eq!(line(2, 6), [".......", ".......", ".......", "...#...", "...#...", "..#....", "..#...."]);
+    assert_eq!(line(0, 4), [".......", ".......", ".......", "..##...", "##.....", ".......", "......."]);
+    assert_eq!(line(0, 2), [".......", ".......", "##.....", "..##...", ".......", ".......", "......."]);
+    assert_eq!(line(2, 0), ["..#....", "..#....", "...#...", "...#...", ".......", ".......", "......."]);
+    assert_eq!(line(4, 0), ["....#..", "....#..", "...#...", "...#...", ".......", ".......", "......."]);
+    assert_eq!(line(3, 3), [".......", ".......", ".......", "...#...", ".......", ".......", "......."]);
+    assert_eq!(line(6, 6), [".......", ".......", ".......", "...#...", "....#..", ".....#.", "......#"]);
+}
+
+#[test]
+fn clipped_lines_match_the_unclipped_ones() {
+    // Drawn on a big sprite and cropped, or on the small one directly, the visible pixels agree.
+    let mut seed = 12345u32;
+    let mut next = |range: i32| {
+        seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+        (seed >> 8) as i32 % range - range / 2
+    };
+    for _ in 0..500 {
+        let (x0, y0, x1, y1) = (next(40), next(40), next(40), next(40));
+        let mut big = GsnSprite::new(60, 60);
+        big.draw_line(x0 + 30, y0 + 30, x1 + 30, y1 + 30, WHITE);
+        let mut small = GsnSprite::new(10, 7);
+        small.draw_line(x0, y0, x1, y1, WHITE);
+        for y in 0..7 {
+            for x in 0..10 {
+                assert_eq!(small.get_pixel(x, y), big.get_pixel(x + 30, y + 30), "{} {} {} {} at {} {}", x0, y0, x1, y1, x, y);
+            }
+        }
+    }
+}
+
+#[test]
+fn huge_lines_only_cost_their_visible_part() {
+    let mut s = GsnSprite::new(8, 4);
+    s.draw_line(-1_000_000_000, 2, 1_000_000_000, 2, WHITE);
+    s.draw_line(i32::MIN, i32::MIN, i32::MAX, i32::MAX, WHITE);
+    assert_eq!(grid(&s), ["#.......", ".#......", "########", "...#...."]);
+}
+
+#[test]
+fn rects() {
+    let mut s = GsnSprite::new(6, 5);
+    s.draw_rect(1, 1, 4, 3, WHITE);
+    assert_eq!(grid(&s), ["......", ".####.", ".#..#.", ".####.", "......"]);
+
+    s.clear(BLACK);
+    s.fill_rect(2, 1, 3, 2, WHITE);
+    assert_eq!(grid(&s), ["......", "..###.", "..###.", "......", "......"]);
+
+    s.clear(BLACK);
+    s.fill_rect(4, 3, 10, 10, WHITE);
+    s.draw_rect(-2, -1, 4, 3, WHITE);
+    assert_eq!(grid(&s), [".#....", "##....", "......", "....##", "....##"]);
+}
+
+#[test]
+fn circles() {
+    let mut s = GsnSprite::new(9, 9);
+    s.draw_circle(4, 4, 3, WHITE);
+    assert_eq!(grid(&s), [
+        ".........",
+        "...###...",
+        "..#...#..",
+        ".#.....#.",
+        ".#.....#.",
+        ".#.....#.",
+        "..#...#..",
+        "...###...",
+        "........."
+    ]);
+    s.clear(BLACK);
+    s.fill_circle(4, 4, 3, WHITE);
+    assert_eq!(grid(&s), [
+        ".........",
+        "...###...",
+        "..#####..",
+        ".#######.",
+        ".#######.",
+        ".#######.",
+        "..#####..",
+        "...###...",
+        "........."
+    ]);
+    s.clear(BLACK);
+    s.draw_circle(4, 4, 0, WHITE);
+    assert_eq!(grid(&s)[4], "....#....");
+}
+
+#[test]
+fn ellipses() {
+    let mut s = GsnSprite::new(11, 7);
+    s.draw_ellipse(5, 3, 4, 2, WHITE);
+    assert_eq!(grid(&s), ["...........", "...#####...", "..#.....#..", ".#.......#.", "..#.....#..", "...#####...", "..........."]);
+    s.clear(BLACK);
+    s.fill_ellipse(5, 3, 4, 2, WHITE);
+    assert_eq!(grid(&s), ["...........", "...#####...", "..#######..", ".#########.", "..#######..", "...#####...", "..........."]);
+    s.clear(BLACK);
+    s.fill_ellipse(5, 3, 3, 0, WHITE);
+    assert_eq!(grid(&s), ["...........", "...........", "...........", "..#######..", "...........", "...........", "..........."]);
+}
+
+#[test]
+fn partially_offscreen_shapes() {
+    let mut s = GsnSprite::new(6, 5);
+    s.draw_circle(6, 5, 2, WHITE);
+    assert_eq!(grid(&s), ["......", "......", "......", ".....#", "....#."]);
+
+    s.clear(BLACK);
+    s.fill_circle(-1, 2, 2, WHITE);
+    assert_eq!(grid(&s), ["#.....", "##....", "##....", "##....", "#....."]);
+
+    // Entirely outside, including huge shapes whose box misses.
+    s.clear(BLACK);
+    s.fill_circle(-10, -10, 3, WHITE);
+    s.fill_ellipse(100, 2, 50, 50, WHITE);
+    s.draw_ellipse(2, -1_000_000, 1_000, 1_000, WHITE);
+    assert!(grid(&s).iter().all(|row| row == "......"));
+}
+
+#[test]
+fn large_ellipses_crossing_the_sprite() {
+    // Over a few pixels an ellipse this big is straight: a column at its left end, rows at its top and bottom.
+    let mut s = GsnSprite::new(7, 7);
+    s.draw_ellipse(50_002, 3, 50_000, 50_000, WHITE);
+    assert!(grid(&s).iter().all(|row| row == "..#...."), "{:?}", grid(&s));
+    s.clear(BLACK);
+    s.fill_ellipse(50_002, 3, 50_000, 50_000, WHITE);
+    assert!(grid(&s).iter().all(|row| row == "..#####"), "{:?}", grid(&s));
+
+    s.clear(BLACK);
+    s.draw_ellipse(3, 3, 50_000, 3, WHITE);
+    assert_eq!(grid(&s), ["#######", ".......", ".......", ".......", ".......", ".......", "#######"]);
+    s.clear(BLACK);
+    s.draw_ellipse(3, 25_000, 3, 50_000, WHITE);
+    assert!(grid(&s).iter().all(|row| row == "#.....#"), "{:?}", grid(&s));
+    // Its tip, the first step off the axis always moves one pixel sideways.
+    s.clear(BLACK);
+    s.draw_ellipse(3, 50_003, 3, 50_000, WHITE);
+    assert_eq!(grid(&s), [".......", ".......", ".......", "...#...", "..#.#..", "..#.#..", "..#.#.."]);
+
+    // The whole sprite inside.
+    s.clear(BLACK);
+    s.fill_ellipse(0, 0, 50_000, 50_000, WHITE);
+    assert!(grid(&s).iter().all(|row| row == "#######"));
+}
@@ -0,0 +1,150 @@
+use engine::error::GsnError;
+use engine::font::GsnFont;
+use engine::renderer::{GsnSprite, BLACK, WHITE};
+
+fn grid(s: &GsnSprite) -> Vec<String> {
+    (0..s.height()).map(|y| (0..s.width()).map(|x| if s.get_pixel(x, y) == Some(BLACK) { '.' } else { '#' }).collect()).collect()
+}
+
+fn assert_font_error(result: Result<GsnFont, GsnError>, what: &str) {
+    let err = result.err();
+    assert!(matches!(err, Some(GsnError::FontDecode(_))), "{}: {:?}", what, err);
+}
+
+const BDF_HEADER: &str = "STARTFONT 2.1
+FONT -test-
+SIZE 4 75 75
+FONTBOUNDINGBOX 4 4 0 -1
+STARTPROPERTIES 3
+FONT_ASCENT 3
+FONT_DESCENT 1
+DEFAULT_CHAR 65
+ENDPROPERTIES
+CHARS 2
+";
+
+const BDF_GLYPHS: &str = "STARTCHAR A
+ENCODING 65
+SWIDTH 500 0
+DWIDTH 5 0
+BBX 3 3 0 0
+BITMAP
+40
+A0
+E0
+ENDCHAR
+STARTCHAR period
+ENCODING 46
+DWIDTH 2 0
+BBX 1 1 1 -1
+BITMAP
+80
+ENDCHAR
+";
+
+fn bdf(header: &str, glyphs: &str) -> Result<GsnFont, GsnError> {
+    GsnFont::from_bdf_bytes(format!("{}{}ENDFONT\n", header, glyphs).as_bytes())
+}
+
+// Rows are one byte each, so at most 8 pixels wide.
+fn psf2(width: u32, height: u32, count: u32, glyph_size: u32, data: &[u8], table: Option<&[u8]>) -> Vec<u8> {
+    let mut out = vec![0x72, 0xb5, 0x4a, 0x86];
+    for v in [0, 32, table.is_some() as u32, count, glyph_size, height, width] {
+        out.extend(v.to_le_bytes());
+    }
+    out.extend(data);
+    out.extend(table.unwrap_or(&[]));
+    out
+}
+
+#[test]
+fn bdf_glyphs_sit_on_the_baseline() {
+    let font = bdf(BDF_HEADER, BDF_GLYPHS).unwrap();
+    assert_eq!((font.line_height(), font.glyph_count()), (4, 2));
+    let mut s = GsnSprite::new(8, 4);
+    s.draw_text(&font, 0, 0, "A.", WHITE, 1);
+    assert_eq!(grid(&s), [".#......", "#.#.....", "###.....", "......#."]);
+    // DEFAULT_CHAR stands in for characters the font lacks.
+    assert_eq!(font.text_size("z", 1), (5, 4));
+}
+
+#[test]
+fn psf1_maps_glyph_numbers_or_its_table() {
+    let mut bytes = vec![0x36, 0x04, 0, 2];
+    bytes.extend([0; 512]);
+    bytes[4 + 65 * 2..4 + 66 * 2].copy_from_slice(&[0x80, 0x40]);
+    let font = GsnFont::from_psf_bytes(&bytes).unwrap();
+    assert_eq!((font.line_height(), font.glyph_count()), (2, 256));
+    let a = font.glyph('A');
+    assert_eq!((a.width(), a.height()), (8, 2));
+    assert!(a.get(0, 0) && a.get(1, 1) && !a.get(1, 0));
+
+    // With a table only the listed code points exist: glyph 65 is 'x' and 'y', a sequence after 0xfffe is skipped.
+    bytes[2] = 0x02;
+    for glyph in 0..256 {
+        if glyph == 65 {
+            for entry in [0x78u16, 0x79, 0xfffe, 0x41, 0x0301] {
+                bytes.extend(entry.to_le_bytes());
+            }
+        }
+        bytes.extend(0xffffu16.to_le_bytes());
+    }
+    let font = GsnFont::from_psf_bytes(&bytes).unwrap();
+    assert_eq!(font.glyph_count(), 2);
+    assert!(font.contains('x') && font.contains('y') && !font.contains('A'));
+    assert!(font.glyph('y').get(1, 1));
+}
+
+#[test]
+fn psf2_reads_its_unicode_table() {
+    let table = ["é".as_bytes(), &[0xff], b"b", &[0xfe], b"q", &[0xff]].concat();
+    let font = GsnFont::from_psf_bytes(&psf2(3, 2, 2, 2, &[0xe0, 0x00, 0x20, 0x20], Some(&table))).unwrap();
+    assert_eq!((font.line_height(), font.glyph_count()), (2, 2));
+    assert!(font.contains('é') && font.contains('b') && !font.contains('q'));
+    let mut s = GsnSprite::new(6, 2);
+    s.draw_text(&font, 0, 0, "éb", WHITE, 1);
+    assert_eq!(grid(&s), ["###..#", ".....#"]);
+
+    // Without a table glyph n is code point n.
+    let font = GsnFont::from_psf_bytes(&psf2(3, 2, 2, 2, &[0xe0, 0x00, 0x20, 0x20], None)).unwrap();
+    assert!(font.contains('\u{0}') && font.contains('\u{1}'));
+}
+
+#[test]
+fn malformed_psf_is_a_decode_error() {
+    assert_font_error(GsnFont::from_psf_bytes(b"not a font"), "garbage");
+    assert_font_error(GsnFont::from_psf_bytes(&[0x36, 0x04, 0]), "psf1 header");
+    assert_font_error(GsnFont::from_psf_bytes(&[0x36, 0x04, 0, 8, 1, 2, 3]), "psf1 glyphs");
+    assert_font_error(GsnFont::from_psf_bytes(&[0x36, 0x04, 0, 0]), "psf1 zero height");
+    assert_font_error(GsnFont::from_psf_bytes(&psf2(3, 2, 2, 2, &[0; 4], None)[..20]), "psf2 header");
+    assert_font_error(GsnFont::from_psf_bytes(&psf2(3, 2, 3, 2, &[0; 4], None)), "psf2 glyphs");
+    assert_font_error(GsnFont::from_psf_bytes(&psf2(3, 2, u32::MAX, 2, &[0; 4], None)), "psf2 huge count");
+    assert_font_error(GsnFont::from_psf_bytes(&psf2(0, 0, u32::MAX, 0, &[], None)), "psf2 zero glyph size");
+    assert_font_error(GsnFont::from_psf_bytes(&psf2(3, 2, 1, 1, &[0; 4], None)), "psf2 glyph size too small");
+    assert_font_error(GsnFont::from_psf_bytes(&psf2(100_000, 1, 1, 12_500, &[0; 12_500], None)), "psf2 huge glyph");
+}
+
+#[test]
+fn malformed_bdf_is_a_decode_error() {
+    assert_font_error(GsnFont::from_bdf_bytes(b"not a font"), "garbage");
+    let cases = [
+        ("STARTCHAR A\nBBX 100000 100000 0 0\nBITMAP\n00\nENDCHAR\n", "missing rows"),
+        ("STARTCHAR A\nBBX 100000 1 0 0\nBITMAP\n00\nENDCHAR\n", "huge glyph"),
+        ("STARTCHAR A\nBBX 8 1 0 0\nBITMAP\n00\n00\nENDCHAR\n", "extra rows"),
+        ("STARTCHAR A\nBBX -1 1 0 0\nBITMAP\n00\nENDCHAR\n", "negative size"),
+        ("STARTCHAR A\nBBX 1 1 0 2147483647\nBITMAP\n00\nENDCHAR\n", "offset overflow"),
+        ("STARTCHAR A\nBBX 8 1 0 0\nBITMAP\nzz\nENDCHAR\n", "bad hex"),
+        ("STARTCHAR A\nBBX 8 1 x 0\nENDCHAR\n", "bad number"),
+        ("STARTCHAR A\nBBX 8 1 0 0\nBITMAP\n00\n", "missing ENDCHAR")
+    ];
+    for (glyphs, what) in cases {
+        assert_font_error(bdf(BDF_HEADER, glyphs), what);
+    }
+
+    let header = |ascent: &str, bounding_box: &str| BDF_HEADER.replace("FONT_ASCENT 3", ascent).replace("FONTBOUNDINGBOX 4 4 0 -1", bounding_box);
+    assert_font_error(bdf(&header("FONT_ASCENT 2147483647", "FONTBOUNDINGBOX 4 4 0 -1"), BDF_GLYPHS), "line height overflow");
+    assert_font_error(bdf(&header("FONT_ASCENT 100000", "FONTBOUNDINGBOX 4 4 0 -1"), BDF_GLYPHS), "huge line height");
+    assert_font_error(bdf(&header("FONT_ASCENT -2147483648", "FONTBOUNDINGBOX 4 4 0 -1"), BDF_GLYPHS), "glyph offset overflow");
+    let header = header("", "FONTBOUNDINGBOX 4 4 0 -2147483648").replace("FONT_DESCENT 1", "");
+    assert_font_error(bdf(&header, BDF_GLYPHS), "descent overflow");
+}
@@ -0,0 +1,84 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use engine::gamepad::{GsnGamepadAxis, GsnGamepadButton, GsnGamepadSource, GsnGamepadState};
+use engine::input::GsnBinding;
+use engine::{GsnEngine, GsnEvent};
+
+struct MockPads(Rc<RefCell<Vec<GsnGamepadState>>>);
+
+impl GsnGamepadSource for MockPads {
+    fn poll(&mut self) -> Vec<GsnGamepadState> {
+        self.0.borrow().clone()
+    }
+}
+
+fn engine_with_pads() -> (GsnEngine, Rc<RefCell<Vec<GsnGamepadState>>>) {
+    let pads = Rc::new(RefCell::new(vec![]));
+    let mut engine = GsnEngine::headless(8, 8);
+    engine.set_gamepad_source(Box::new(MockPads(pads.clone())));
+    engine.actions.clear();
+    (engine, pads)
+}
+
+#[test]
+fn connect_and_disconnect_become_events() {
+    let (mut engine, pads) = engine_with_pads();
+    pads.borrow_mut().push(GsnGamepadState::new(2, "Pad"));
+    engine.update();
+    assert!(matches!(engine.actions[0], GsnEvent::GamepadConnected(2)));
+    assert_eq!(engine.gamepad(2).map(|p| p.name()), Some("Pad"));
+
+    engine.actions.clear();
+    engine.update();
+    assert!(!engine.actions.iter().any(|e| matches!(e, GsnEvent::GamepadConnected(_))));
+
+    pads.borrow_mut().clear();
+    engine.actions.clear();
+    engine.update();
+    assert!(matches!(engine.actions[0], GsnEvent::GamepadDisconnected(2)));
+    assert!(engine.gamepads().is_empty());
+}
+
+#[test]
+fn button_edges_and_bindings() {
+    let (mut engine, pads) = engine_with_pads();
+    engine.input_map.bind("jump", GsnBinding::Gamepad(GsnGamepadButton::A)).unwrap();
+    let mut pad = GsnGamepadState::new(0, "Pad");
+    pads.borrow_mut().push(pad.clone());
+    engine.update();
+
+    pad.buttons[GsnGamepadButton::A as usize] = true;
+    *pads.borrow_mut() = vec![pad.clone()];
+    engine.update();
+    assert!(engine.gamepad_pressed(0, GsnGamepadButton::A));
+    assert!(engine.gamepad_held(0, GsnGamepadButton::A));
+    assert!(engine.action_pressed("jump"));
+
+    engine.update();
+    assert!(!engine.gamepad_pressed(0, GsnGamepadButton::A));
+    assert!(engine.action_held("jump"));
+
+    pads.borrow_mut().clear();
+    engine.update();
+    assert!(engine.gamepad_released(0, GsnGamepadButton::A));
+    assert!(engine.action_released("jump"));
+    assert!(!engine.action_held("jump"));
+}
+
+#[test]
+fn deadzones() {
+    let (mut engine, pads) = engine_with_pads();
+    let mut pad = GsnGamepadState::new(0, "Pad");
+    pad.axes[GsnGamepadAxis::LeftX as usize] = 0.1;
+    pad.axes[GsnGamepadAxis::LeftY as usize] = 0.1;
+    pad.axes[GsnGamepadAxis::RightX as usize] = 1.0;
+    pad.axes[GsnGamepadAxis::RightTrigger as usize] = 0.0;
+    pads.borrow_mut().push(pad);
+    engine.update();
+
+    assert_eq!(engine.gamepad_axis(0, GsnGamepadAxis::LeftX), 0.0);
+    assert_eq!(engine.gamepad_axis(0, GsnGamepadAxis::RightX), 1.0);
+    assert_eq!(engine.gamepad_axis(0, GsnGamepadAxis::LeftTrigger), 0.0);
+    assert!((engine.gamepad_axis(0, GsnGamepadAxis::RightTrigger) - 0.375).abs() < 1e-6);
+    assert_eq!(engine.gamepad_axis(1, GsnGamepadAxis::RightX), 0.0);
+}
@@ -0,0 +1,35 @@
+use engine::headless::new_gsn_headless;
+use engine::renderer::{pixel_rgb, GsnBackend, GsnSprite, BLACK, RED};
+use engine::GsnEngine;
+
+#[test]
+fn drawing_lands_in_the_headless_buffer() {
+    let mut engine = GsnEngine::headless(4, 3);
+    assert_eq!((engine.width(), engine.height()), (4, 3));
+    engine.buffer().set_pixel(3, 2, RED);
+    engine.render();
+    assert_eq!(engine.buffer().get_pixel(3, 2), Some(RED));
+    assert_eq!(engine.buffer().get_pixel(0, 0), Some(BLACK));
+    assert_eq!(engine.frames(), 1);
+}
+
+#[test]
+fn backend_can_be_driven_directly() {
+    let mut backend = new_gsn_headless();
+    backend.initialize(2, 2).unwrap();
+    backend.buffer_mut().clear(pixel_rgb(1, 2, 3));
+    backend.render();
+    assert_eq!(backend.buffer().to_rgba8(), [1, 2, 3, 255].repeat(4));
+}
+
+#[test]
+fn reads_outside_the_sprite_are_none() {
+    let mut sprite = GsnSprite::new(3, 2);
+    sprite.set_pixel(2, 1, RED);
+    assert_eq!(sprite.get_pixel(2, 1), Some(RED));
+    assert_eq!(sprite.get_pixel(3, 0), None);
+    assert_eq!(sprite.get_pixel(0, 2), None);
+    assert_eq!(sprite.get_pixel(3, 2), None);
+    assert!(!sprite.set_pixel(3, 2, RED));
+    assert_eq!(GsnSprite::new(0, 0).get_pixel(0, 0), None);
+}
@@ -0,0 +1,142 @@
+use engine::error::GsnError;
+use engine::renderer::{pixel_rgba, GsnSprite};
+
+// Runs, repeats and small and large steps, so every RLE packet and QOI op shows up.
+fn sample() -> GsnSprite {
+    let mut s = GsnSprite::new(7, 5);
+    for y in 0..5 {
+        for x in 0..7 {
+            let p = match (x + y * 7) % 6 {
+                0 | 1 => pixel_rgba(10, 20, 30, 255),
+                2 => pixel_rgba(11, 21, 29, 255),
+                3 => pixel_rgba(40 + x as u8, 25, 35, 255),
+                4 => pixel_rgba(200, 5, 90 + y as u8 * 30, 128),
+                _ => pixel_rgba(x as u8 * 40, y as u8 * 60, 7, 0)
+            };
+            s.set_pixel(x, y, p);
+        }
+    }
+    s
+}
+
+type Decoder = fn(&[u8]) -> Result<GsnSprite, GsnError>;
+
+fn pixels(s: &GsnSprite) -> Vec<[u8; 4]> {
+    (0..s.height()).flat_map(|y| (0..s.width()).map(move |x| (x, y)))
+        .map(|(x, y)| s.get_pixel(x, y).unwrap())
+        .map(|p| [p.r(), p.g(), p.b(), p.a()])
+        .collect()
+}
+
+fn assert_decode_error(result: Result<GsnSprite, GsnError>, what: &str) {
+    let err = result.err();
+    assert!(matches!(err, Some(GsnError::ImageDecode(_))), "{}: {:?}", what, err);
+}
+
+#[test]
+fn lossless_formats_round_trip() {
+    let s = sample();
+    for (name, decoded) in [
+        ("bmp", GsnSprite::from_bmp_bytes(&s.to_bmp_bytes().unwrap())),
+        ("tga", GsnSprite::from_tga_bytes(&s.to_tga_bytes().unwrap())),
+        ("qoi", GsnSprite::from_qoi_bytes(&s.to_qoi_bytes()))
+    ] {
+        let decoded = decoded.unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (7, 5), "{}", name);
+        assert_eq!(pixels(&decoded), pixels(&s), "{}", name);
+    }
+}
+
+#[test]
+fn netpbm_round_trips_without_alpha() {
+    let s = sample();
+    let ppm = GsnSprite::from_ppm_bytes(&s.to_ppm_bytes()).unwrap();
+    let opaque: Vec<[u8; 4]> = pixels(&s).into_iter().map(|[r, g, b, _]| [r, g, b, 255]).collect();
+    assert_eq!(pixels(&ppm), opaque);
+
+    let pgm = GsnSprite::from_ppm_bytes(&s.to_pgm_bytes()).unwrap();
+    let luma: Vec<[u8; 4]> = pixels(&s).into_iter().map(|[r, g, b, _]| {
+        let l = ((299 * r as u32 + 587 * g as u32 + 114 * b as u32 + 500) / 1000) as u8;
+        [l, l, l, 255]
+    }).collect();
+    assert_eq!(pixels(&pgm), luma);
+}
+
+#[test]
+fn truncated_files_are_decode_errors() {
+    let s = sample();
+    let decoders: [(&str, Vec<u8>, Decoder); 4] = [
+        ("bmp", s.to_bmp_bytes().unwrap(), GsnSprite::from_bmp_bytes),
+        ("tga", s.to_tga_bytes().unwrap(), GsnSprite::from_tga_bytes),
+        ("qoi", s.to_qoi_bytes(), GsnSprite::from_qoi_bytes),
+        ("ppm", s.to_ppm_bytes(), GsnSprite::from_ppm_bytes)
+    ];
+    for (name, bytes, decode) in decoders {
+        // The QOI end marker is 8 bytes and optional to the decoder, so the last cut lands in pixel data for all four.
+        for len in [0, 1, 5, 12, bytes.len() / 2, bytes.len() - 9] {
+            assert_decode_error(decode(&bytes[..len]), &format!("{} cut to {}", name, len));
+        }
+    }
+}
+
+#[test]
+fn oversized_headers_are_decode_errors() {
+    let s = sample();
+
+    let mut bmp = s.to_bmp_bytes().unwrap();
+    bmp[18..22].copy_from_slice(&100_000i32.to_le_bytes());
+    bmp[22..26].copy_from_slice(&(-100_000i32).to_le_bytes());
+    assert_decode_error(GsnSprite::from_bmp_bytes(&bmp), "bmp");
+
+    let mut tga = s.to_tga_bytes().unwrap();
+    tga[12..16].copy_from_slice(&[0xFF; 4]);
+    assert_decode_error(GsnSprite::from_tga_bytes(&tga), "tga");
+
+    let mut qoi = s.to_qoi_bytes();
+    qoi[4..12].copy_from_slice(&[0xFF; 8]);
+    assert_decode_error(GsnSprite::from_qoi_bytes(&qoi), "qoi");
+
+    assert_decode_error(GsnSprite::from_ppm_bytes(b"P6\n100000 100000\n255\n"), "ppm");
+    assert_decode_error(GsnSprite::from_ppm_bytes(b"P6\n99999999999999999999 1\n255\n"), "ppm overflow");
+}
+
+// Headers claiming 16384x16384, the most check_size lets through, followed by no pixel data.
+// These have to fail on the missing data instead of reserving a GiB for pixels that aren't there.
+#[test]
+fn truncated_headers_are_decode_errors() {
+    let s = sample();
+    let side = 16384u32;
+
+    let mut bmp = s.to_bmp_bytes().unwrap();
+    bmp[18..22].copy_from_slice(&(side as i32).to_le_bytes());
+    bmp[22..26].copy_from_slice(&(-(side as i32)).to_le_bytes());
+    let data_offset = u32::from_le_bytes([bmp[10], bmp[11], bmp[12], bmp[13]]) as usize;
+    assert_decode_error(GsnSprite::from_bmp_bytes(&bmp[..data_offset]), "bmp");
+
+    let mut tga = s.to_tga_bytes().unwrap();
+    tga[12..14].copy_from_slice(&(side as u16).to_le_bytes());
+    tga[14..16].copy_from_slice(&(side as u16).to_le_bytes());
+    assert_decode_error(GsnSprite::from_tga_bytes(&tga[..19]), "rle tga");
+    tga[2] &= !8;
+    assert_decode_error(GsnSprite::from_tga_bytes(&tga[..19]), "raw tga");
+
+    let mut qoi = s.to_qoi_bytes();
+    qoi[4..8].copy_from_slice(&side.to_be_bytes());
+    qoi[8..12].copy_from_slice(&side.to_be_bytes());
+    assert_decode_error(GsnSprite::from_qoi_bytes(&qoi[..15]), "qoi");
+
+    assert_decode_error(GsnSprite::from_ppm_bytes(b"P6\n16384 16384\n255\n\0\0\0"), "binary ppm");
+    assert_decode_error(GsnSprite::from_ppm_bytes(b"P3\n16384 16384\n255\n0 0 0\n"), "ascii ppm");
+}
+
+#[test]
+fn sizes_the_format_cannot_hold_are_encode_errors() {
+    let wide = GsnSprite::new(u16::MAX as u32 + 1, 1);
+    assert!(matches!(wide.to_tga_bytes(), Err(GsnError::ImageEncode(_))));
+    assert!(wide.to_bmp_bytes().is_ok());
+    assert!(GsnSprite::new(u16::MAX as u32, 1).to_tga_bytes().is_ok());
+
+    // No pixels get allocated with a zero height, only the header has to refuse the width.
+    let huge = GsnSprite::new(i32::MAX as u32 + 1, 0);
+    assert!(matches!(huge.to_bmp_bytes(), Err(GsnError::ImageEncode(_))));
+}
@@ -0,0 +1,119 @@
+use engine::{GsnAction, GsnEngine, GsnEvent, GsnKey, GsnModifiers, MousePos};
+use engine::input::{GsnBinding, GsnInputMap};
+
+fn key(engine: &mut GsnEngine, key: GsnKey, action: GsnAction) {
+    engine.push_event(GsnEvent::KeyPress(key, action, GsnModifiers::default()));
+}
+
+fn button(engine: &mut GsnEngine, button: u8, action: GsnAction) {
+    engine.push_event(GsnEvent::MousePress(button, action, MousePos { x: 0, y: 0 }, GsnModifiers::default()));
+}
+
+#[test]
+fn key_edges_last_one_update() {
+    let mut engine = GsnEngine::headless(8, 8);
+    key(&mut engine, GsnKey::Space, GsnAction::Press);
+    assert!(engine.key_pressed(GsnKey::Space));
+    assert!(engine.key_held(GsnKey::Space));
+    assert!(!engine.key_released(GsnKey::Space));
+
+    engine.update();
+    assert!(!engine.key_pressed(GsnKey::Space));
+    assert!(engine.key_held(GsnKey::Space));
+
+    key(&mut engine, GsnKey::Space, GsnAction::Release);
+    assert!(engine.key_released(GsnKey::Space));
+    assert!(!engine.key_held(GsnKey::Space));
+
+    engine.update();
+    assert!(!engine.key_released(GsnKey::Space));
+}
+
+#[test]
+fn tap_within_one_update_is_seen() {
+    let mut engine = GsnEngine::headless(8, 8);
+    key(&mut engine, GsnKey::A, GsnAction::Press);
+    key(&mut engine, GsnKey::A, GsnAction::Release);
+    assert!(engine.key_pressed(GsnKey::A));
+    assert!(engine.key_released(GsnKey::A));
+    assert!(!engine.key_held(GsnKey::A));
+}
+
+#[test]
+fn repeat_counts_press_and_auto_repeat() {
+    let mut engine = GsnEngine::headless(8, 8);
+    key(&mut engine, GsnKey::Down, GsnAction::Press);
+    assert!(engine.key_repeated(GsnKey::Down));
+    engine.update();
+    assert!(!engine.key_repeated(GsnKey::Down));
+    key(&mut engine, GsnKey::Down, GsnAction::Repeat);
+    assert!(engine.key_repeated(GsnKey::Down));
+    assert!(!engine.key_pressed(GsnKey::Down));
+    assert!(engine.key_held(GsnKey::Down));
+}
+
+#[test]
+fn mouse_edges_last_one_update() {
+    let mut engine = GsnEngine::headless(8, 8);
+    button(&mut engine, 1, GsnAction::Press);
+    assert!(engine.mouse_pressed(1));
+    assert!(engine.mouse_held(1));
+    assert!(!engine.mouse_held(2));
+
+    engine.update();
+    assert!(!engine.mouse_pressed(1));
+    assert!(engine.mouse_held(1));
+
+    button(&mut engine, 1, GsnAction::Release);
+    assert!(engine.mouse_released(1));
+    assert!(!engine.mouse_held(1));
+}
+
+#[test]
+fn actions_follow_any_binding() {
+    let mut engine = GsnEngine::headless(8, 8);
+    engine.input_map.bind("jump", GsnBinding::Key(GsnKey::Space)).unwrap();
+    engine.input_map.bind("jump", GsnBinding::Mouse(1)).unwrap();
+
+    button(&mut engine, 1, GsnAction::Press);
+    assert!(engine.action_pressed("jump"));
+    assert!(engine.action_held("jump"));
+    key(&mut engine, GsnKey::Space, GsnAction::Press);
+    engine.update();
+    button(&mut engine, 1, GsnAction::Release);
+    assert!(engine.action_held("jump"));
+    assert!(!engine.action_released("jump"));
+    key(&mut engine, GsnKey::Space, GsnAction::Release);
+    assert!(engine.action_released("jump"));
+    assert!(!engine.action_held("missing"));
+}
+
+#[test]
+fn input_map_config_round_trip() {
+    let map: GsnInputMap = "# controls\nmove_left = key:A key:left\njump = key:Space mouse:1\nunbound =\n".parse().unwrap();
+    assert_eq!(map.bindings("move_left"), &[GsnBinding::Key(GsnKey::A), GsnBinding::Key(GsnKey::Left)]);
+    assert!(map.bindings("unbound").is_empty());
+    assert_eq!(map.actions().count(), 3);
+    assert_eq!(map.to_string().parse::<GsnInputMap>().unwrap(), map);
+
+    assert!("jump = key:Nope".parse::<GsnInputMap>().unwrap_err().to_string().contains("line 1"));
+    assert!("jump key:A".parse::<GsnInputMap>().is_err());
+    assert!("jump = mouse:x".parse::<GsnInputMap>().is_err());
+}
+
+#[test]
+fn bound_names_survive_save_and_load() {
+    let path = std::env::temp_dir().join(format!("gsn_input_map_{}.cfg", std::process::id()));
+    let mut map = GsnInputMap::new();
+    map.bind("move_left", GsnBinding::Key(GsnKey::A)).unwrap();
+    map.bind("fire2", GsnBinding::Mouse(1)).unwrap();
+    for name in ["", "move left", "a=b", "fire#2", "jump\n"] {
+        assert!(map.bind(name, GsnBinding::Key(GsnKey::Space)).is_err(), "{:?}", name);
+    }
+    assert_eq!(map.actions().count(), 2);
+
+    map.save(&path).unwrap();
+    let loaded = GsnInputMap::load(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.unwrap(), map);
+}
@@ -0,0 +1,199 @@
+#![cfg(feature = "jpeg")]
+use engine::error::GsnError;
+use engine::renderer::{pixel_rgb, GsnSprite};
+
+// Huffman bit writer with 0xFF byte stuffing.
+struct Bits {
+    out: Vec<u8>,
+    acc: u32,
+    n: u32
+}
+
+impl Bits {
+    fn new() -> Bits {
+        Bits { out: Vec::new(), acc: 0, n: 0 }
+    }
+
+    fn put(&mut self, value: u32, len: u32) {
+        for i in (0..len).rev() {
+            self.acc = (self.acc << 1) | ((value >> i) & 1);
+            self.n += 1;
+            if self.n == 8 {
+                let b = self.acc as u8;
+                self.out.push(b);
+                if b == 0xFF {
+                    self.out.push(0);
+                }
+                self.acc = 0;
+                self.n = 0;
+            }
+        }
+    }
+
+    // The category table below gives every category a 4 bit code equal to its number.
+    fn diff(&mut self, d: i32) {
+        let s = 32 - d.unsigned_abs().leading_zeros();
+        self.put(s, 4);
+        let v = if d >= 0 { d } else { d + (1 << s) - 1 };
+        self.put(v as u32, s);
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        while self.n != 0 {
+            self.put(1, 1);
+        }
+        self.out
+    }
+}
+
+fn segment(out: &mut Vec<u8>, marker: u8, payload: &[u8]) {
+    out.extend([0xFF, marker]);
+    out.extend(((payload.len() + 2) as u16).to_be_bytes());
+    out.extend(payload);
+}
+
+fn tables(out: &mut Vec<u8>) {
+    // DC table 0: categories 0-12, all 4 bits long.
+    let mut dc = vec![0x00, 0, 0, 0, 13];
+    dc.extend([0; 12]);
+    dc.extend(0..=12);
+    segment(out, 0xC4, &dc);
+    // AC table 0: only EOB, 1 bit long.
+    let mut ac = vec![0x10, 1];
+    ac.extend([0; 15]);
+    ac.push(0x00);
+    segment(out, 0xC4, &ac);
+}
+
+// Grayscale JPEG made of flat 8x8 blocks, levels are the blocks in row-major order.
+fn gray_jpeg(progressive: bool, blocks_w: u16, levels: &[u8], app1: Option<&[u8]>) -> Vec<u8> {
+    let blocks_h = levels.len() as u16 / blocks_w;
+    let mut out = vec![0xFF, 0xD8];
+    if let Some(app1) = app1 {
+        segment(&mut out, 0xE1, app1);
+    }
+    let mut dqt = vec![0];
+    dqt.extend([1; 64]);
+    segment(&mut out, 0xDB, &dqt);
+    let mut sof = vec![8];
+    sof.extend((blocks_h * 8).to_be_bytes());
+    sof.extend((blocks_w * 8).to_be_bytes());
+    sof.extend([1, 1, 0x11, 0]);
+    segment(&mut out, if progressive { 0xC2 } else { 0xC0 }, &sof);
+    tables(&mut out);
+
+    // With a quantizer of 1 a flat block's DC coefficient is 8 * (level - 128).
+    let dc_scan = |with_eob: bool| {
+        let mut bits = Bits::new();
+        let mut prev = 0;
+        for &level in levels {
+            let dc = 8 * (level as i32 - 128);
+            bits.diff(dc - prev);
+            prev = dc;
+            if with_eob {
+                bits.put(0, 1);
+            }
+        }
+        bits.finish()
+    };
+    if progressive {
+        segment(&mut out, 0xDA, &[1, 1, 0x00, 0, 0, 0]);
+        out.extend(dc_scan(false));
+        let mut bits = Bits::new();
+        for _ in levels {
+            bits.put(0, 1);
+        }
+        segment(&mut out, 0xDA, &[1, 1, 0x00, 1, 63, 0]);
+        out.extend(bits.finish());
+    } else {
+        segment(&mut out, 0xDA, &[1, 1, 0x00, 0, 63, 0]);
+        out.extend(dc_scan(true));
+    }
+    out.extend([0xFF, 0xD9]);
+    out
+}
+
+// One row of lossless grayscale samples, predicted from the left neighbour.
+fn lossless_row(precision: u8, samples: &[u16]) -> Vec<u8> {
+    let mut out = vec![0xFF, 0xD8];
+    let mut sof = vec![precision];
+    sof.extend(1u16.to_be_bytes());
+    sof.extend((samples.len() as u16).to_be_bytes());
+    sof.extend([1, 1, 0x11, 0]);
+    segment(&mut out, 0xC3, &sof);
+    tables(&mut out);
+    segment(&mut out, 0xDA, &[1, 1, 0x00, 1, 0, 0]);
+    let mut bits = Bits::new();
+    let mut prev = 1i32 << (precision - 1);
+    for &s in samples {
+        bits.diff(s as i32 - prev);
+        prev = s as i32;
+    }
+    out.extend(bits.finish());
+    out.extend([0xFF, 0xD9]);
+    out
+}
+
+fn levels(sprite: &GsnSprite) -> Vec<u8> {
+    let mut out = Vec::new();
+    for y in (0..sprite.height()).step_by(8) {
+        for x in (0..sprite.width()).step_by(8) {
+            let p = sprite.get_pixel(x + 3, y + 5).unwrap();
+            assert_eq!((p.r(), p.g(), p.a()), (p.b(), p.b(), 255));
+            out.push(p.r());
+        }
+    }
+    out
+}
+
+#[test]
+fn baseline_and_progressive_decode_the_same() {
+    let blocks = [0, 64, 128, 255, 30, 200];
+    for progressive in [false, true] {
+        let sprite = GsnSprite::from_jpeg_bytes(&gray_jpeg(progressive, 3, &blocks, None)).unwrap();
+        assert_eq!((sprite.width(), sprite.height()), (24, 16));
+        assert_eq!(levels(&sprite), blocks, "progressive: {}", progressive);
+    }
+}
+
+#[test]
+fn exif_orientation_is_applied() {
+    // Big endian TIFF with a single IFD0 entry: orientation 6, rotate 90 degrees clockwise.
+    let mut exif = b"Exif\0\0MM\0\x2a\0\0\0\x08\0\x01".to_vec();
+    exif.extend([0x01, 0x12, 0, 3, 0, 0, 0, 1, 0, 6, 0, 0, 0, 0, 0, 0]);
+    let sprite = GsnSprite::from_jpeg_bytes(&gray_jpeg(false, 2, &[0, 255], Some(&exif))).unwrap();
+    assert_eq!((sprite.width(), sprite.height()), (8, 16));
+    assert_eq!(sprite.get_pixel(4, 2), Some(pixel_rgb(0, 0, 0)));
+    assert_eq!(sprite.get_pixel(4, 12), Some(pixel_rgb(255, 255, 255)));
+}
+
+#[test]
+fn high_precision_samples_use_the_full_range() {
+    let sprite = GsnSprite::from_jpeg_bytes(&lossless_row(12, &[0, 2048, 4095])).unwrap();
+    let row: Vec<u8> = (0..3).map(|x| sprite.get_pixel(x, 0).unwrap().r()).collect();
+    assert_eq!(row, [0, 128, 255]);
+
+    let sprite = GsnSprite::from_jpeg_bytes(&lossless_row(10, &[0, 512, 1023])).unwrap();
+    let row: Vec<u8> = (0..3).map(|x| sprite.get_pixel(x, 0).unwrap().r()).collect();
+    assert_eq!(row, [0, 128, 255]);
+}
+
+#[test]
+fn broken_jpegs_are_decode_errors() {
+    let jpeg = gray_jpeg(false, 2, &[10, 20], None);
+    for len in [0, 2, 10, 40, jpeg.len() / 2] {
+        let err = GsnSprite::from_jpeg_bytes(&jpeg[..len]).err();
+        assert!(matches!(err, Some(GsnError::ImageDecode(_))), "len {}: {:?}", len, err);
+    }
+    let err = GsnSprite::from_jpeg_bytes(b"definitely not a jpeg").err();
+    assert!(matches!(err, Some(GsnError::ImageDecode(_))), "{:?}", err);
+}
+
+#[test]
+fn oversized_frames_are_rejected_before_decoding() {
+    let mut jpeg = gray_jpeg(false, 1, &[10], None);
+    let sof = jpeg.windows(2).position(|w| w == [0xFF, 0xC0]).unwrap();
+    jpeg[sof + 5..sof + 9].copy_from_slice(&[0xFF, 0xFF, 0xFF, 0xFF]);
+    let err = GsnSprite::from_jpeg_bytes(&jpeg).err();
+    assert!(matches!(err, Some(GsnError::ImageDecode(_))), "{:?}", err);
+}
@@ -0,0 +1,43 @@
+use engine::renderer::{letterbox, GsnScaleMode, GsnSprite, GsnViewport, WHITE};
+use engine::GsnEngine;
+
+#[test]
+fn integer_scaling_centres_whole_multiples() {
+    // 320x180 into 1280x800 is 4x with 40 pixels of bars top and bottom.
+    assert_eq!(letterbox(320, 180, 1280, 800, GsnScaleMode::Integer), GsnViewport { x: 0, y: 40, width: 1280, height: 720 });
+    assert_eq!(letterbox(320, 180, 1000, 700, GsnScaleMode::Integer), GsnViewport { x: 20, y: 80, width: 960, height: 540 });
+    // Smaller than the buffer falls back to fitting.
+    assert_eq!(letterbox(320, 180, 160, 160, GsnScaleMode::Integer), GsnViewport { x: 0, y: 35, width: 160, height: 90 });
+}
+
+#[test]
+fn fit_and_stretch() {
+    assert_eq!(letterbox(320, 180, 1000, 700, GsnScaleMode::Fit), GsnViewport { x: 0, y: 68, width: 1000, height: 563 });
+    assert_eq!(letterbox(320, 180, 1000, 700, GsnScaleMode::Stretch), GsnViewport { x: 0, y: 0, width: 1000, height: 700 });
+}
+
+#[test]
+fn headless_mouse_maps_one_to_one() {
+    let engine = GsnEngine::headless(64, 32);
+    let p = engine.window_to_buffer(10.5, 20.0);
+    assert_eq!((p.x, p.y), (10, 20));
+    let p = engine.window_to_buffer(-5.0, 100.0);
+    assert_eq!((p.x, p.y), (0, 31));
+}
+
+#[test]
+fn cursor_and_images_share_the_top_row() {
+    // The first row of a decoded image lands on buffer row 0, which is where a cursor at the top edge points.
+    let mut rgba = vec![0; 4 * 4 * 4];
+    rgba[..16].fill(255);
+    let image = GsnSprite::from_rgba8(4, 4, &rgba).unwrap();
+    let mut engine = GsnEngine::headless(4, 4);
+    engine.buffer().draw_sprite(&image, 0, 0);
+
+    let top = engine.window_to_buffer(2.0, 0.0);
+    assert_eq!((top.x, top.y), (2, 0));
+    assert_eq!(engine.buffer().get_pixel(top.x, top.y), Some(WHITE));
+    let bottom = engine.window_to_buffer(2.0, 3.5);
+    assert_eq!(bottom.y, 3);
+    assert_ne!(engine.buffer().get_pixel(bottom.x, bottom.y), Some(WHITE));
+}
@@ -0,0 +1,29 @@
+use std::mem::size_of;
+use engine::blend::GsnBlendMode;
+use engine::renderer::{pixel_rgba, GsnSprite, Pixel, RED};
+
+#[test]
+fn pixels_are_packed_rgba8() {
+    assert_eq!(size_of::<Pixel>(), 4);
+    let mut sprite = GsnSprite::new(2, 1);
+    sprite.set_pixel(1, 0, pixel_rgba(1, 2, 3, 4));
+    assert_eq!(sprite.as_bytes(), &[0, 0, 0, 255, 1, 2, 3, 4]);
+}
+
+#[test]
+fn float_helpers_round_trip() {
+    assert_eq!(Pixel::from_f32(1.0, 0.0, 0.0, 1.0), RED);
+    assert_eq!(Pixel::from_f32(0.5, -1.0, 2.0, 0.2), pixel_rgba(128, 0, 255, 51));
+    for c in 0..=255 {
+        let [r, g, b, a] = pixel_rgba(c, 255 - c, c, 255).to_f32();
+        assert_eq!(Pixel::from_f32(r, g, b, a), pixel_rgba(c, 255 - c, c, 255));
+    }
+}
+
+#[test]
+fn alpha_blending_rounds_to_nearest() {
+    let mut sprite = GsnSprite::new(1, 1);
+    sprite.set_blend_mode(GsnBlendMode::Alpha);
+    sprite.set_pixel(0, 0, pixel_rgba(255, 255, 255, 128));
+    assert_eq!(sprite.get_pixel(0, 0), Some(pixel_rgba(128, 128, 128, 255)));
+}
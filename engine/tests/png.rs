@@ -0,0 +1,57 @@
+#![cfg(feature = "png")]
+use engine::error::GsnError;
+use engine::renderer::{pixel_rgb, pixel_rgba, GsnSprite};
+
+fn sample() -> GsnSprite {
+    let mut s = GsnSprite::new(3, 2);
+    s.set_pixel(0, 0, pixel_rgb(255, 0, 0));
+    s.set_pixel(1, 0, pixel_rgba(1, 2, 3, 4));
+    s.set_pixel(2, 1, pixel_rgba(200, 100, 50, 0));
+    s
+}
+
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &b in bytes {
+        crc ^= b as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+// Rewrites the IHDR size and its checksum, the chunk starts right after the 8 byte signature.
+fn with_size(mut png: Vec<u8>, width: u32, height: u32) -> Vec<u8> {
+    png[16..20].copy_from_slice(&width.to_be_bytes());
+    png[20..24].copy_from_slice(&height.to_be_bytes());
+    let crc = crc32(&png[12..29]);
+    png[29..33].copy_from_slice(&crc.to_be_bytes());
+    png
+}
+
+#[test]
+fn round_trip() {
+    let png = sample().to_png_bytes().unwrap();
+    let back = GsnSprite::from_png_bytes(&png).unwrap();
+    assert_eq!((back.width(), back.height()), (3, 2));
+    assert_eq!(back.to_rgba8(), sample().to_rgba8());
+}
+
+#[test]
+fn truncated_and_garbage_are_errors() {
+    let png = sample().to_png_bytes().unwrap();
+    // Cuts in the signature, the header and the image data.
+    for len in [0, 8, 20, 33, png.len() - 20] {
+        assert!(matches!(GsnSprite::from_png_bytes(&png[..len]), Err(GsnError::ImageDecode(_))), "{} bytes", len);
+    }
+    assert!(matches!(GsnSprite::from_png_bytes(b"not a png at all"), Err(GsnError::ImageDecode(_))));
+}
+
+#[test]
+fn oversized_headers_are_rejected_before_allocating() {
+    let png = sample().to_png_bytes().unwrap();
+    for (w, h) in [(100_000, 100_000), (0x7FFF_FFFF, 1), (1, 0x7FFF_FFFF)] {
+        assert!(matches!(GsnSprite::from_png_bytes(&with_size(png.clone(), w, h)), Err(GsnError::ImageDecode(_))), "{}x{}", w, h);
+    }
+}
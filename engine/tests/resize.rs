@@ -0,0 +1,36 @@
+use engine::blend::GsnBlendMode;
+use engine::renderer::{GsnResizePolicy, GsnViewport};
+use engine::{GsnEngine, GsnEvent};
+
+#[test]
+fn keep_scales_the_same_buffer() {
+    let mut engine = GsnEngine::headless(320, 180);
+    engine.actions.clear();
+    engine.resize(1280, 800);
+    assert_eq!((engine.width(), engine.height()), (320, 180));
+    assert_eq!(engine.viewport(), GsnViewport { x: 0, y: 40, width: 1280, height: 720 });
+    assert!(matches!(engine.actions[..], [GsnEvent::Resized(1280, 800)]));
+}
+
+#[test]
+fn grow_reallocates_the_buffer() {
+    let mut engine = GsnEngine::headless(320, 180);
+    engine.set_resize_policy(GsnResizePolicy::Grow);
+    engine.buffer().set_pixel(0, 0, engine::renderer::WHITE);
+    engine.buffer().set_blend_mode(GsnBlendMode::Alpha);
+    engine.resize(640, 400);
+    assert_eq!((engine.width(), engine.height()), (640, 400));
+    assert_eq!(engine.buffer().get_pixel(0, 0), Some(engine::renderer::BLACK));
+    assert!(matches!(engine.buffer().blend_mode(), GsnBlendMode::Alpha));
+    assert_eq!(engine.viewport(), GsnViewport { x: 0, y: 0, width: 640, height: 400 });
+    let p = engine.window_to_buffer(639.0, 399.0);
+    assert_eq!((p.x, p.y), (639, 399));
+}
+
+#[test]
+fn minimized_window_keeps_the_buffer() {
+    let mut engine = GsnEngine::headless(320, 180);
+    engine.set_resize_policy(GsnResizePolicy::Grow);
+    engine.resize(0, 0);
+    assert_eq!((engine.width(), engine.height()), (320, 180));
+}
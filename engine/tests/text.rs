@@ -0,0 +1,103 @@
+use engine::error::GsnError;
+use engine::font::{text_size, GsnFont, GsnGlyph};
+use engine::renderer::{GsnSprite, BLACK, WHITE};
+
+fn grid(s: &GsnSprite) -> Vec<String> {
+    (0..s.height()).map(|y| (0..s.width()).map(|x| if s.get_pixel(x, y) == Some(BLACK) { '.' } else { '#' }).collect()).collect()
+}
+
+fn glyph(rows: &[&str]) -> GsnGlyph {
+    let bitmap = rows.iter().flat_map(|row| row.chars().map(|c| c == '#')).collect();
+    GsnGlyph::new(rows[0].len() as u32, rows.len() as u32, bitmap).unwrap()
+}
+
+// Three pixel lines: a 2x3 'a', a single pixel 'b' one row down with a wider advance, and a 1 pixel space.
+fn font() -> GsnFont {
+    let mut font = GsnFont::new(3);
+    font.insert('a', glyph(&["#.", ".#", "##"]));
+    let mut b = glyph(&["#"]);
+    b.y_offset = 1;
+    b.advance = 2;
+    font.insert('b', b);
+    font.insert(' ', glyph(&["."]));
+    font
+}
+
+#[test]
+fn glyphs_check_their_bitmap() {
+    let err = GsnGlyph::new(2, 2, vec![true; 3]).err();
+    assert!(matches!(err, Some(GsnError::FontDecode(_))), "{:?}", err);
+    assert!(GsnGlyph::new(0, 5, Vec::new()).is_ok());
+
+    let g = glyph(&["#.", ".#"]);
+    assert_eq!((g.width(), g.height(), g.advance), (2, 2, 2));
+    assert!(g.get(0, 0) && g.get(1, 1) && !g.get(1, 0));
+    assert!(!g.get(2, 0) && !g.get(0, 2));
+}
+
+#[test]
+fn layout_follows_advances_lines_and_tabs() {
+    let font = font();
+    assert_eq!(font.text_size("", 1), (0, 0));
+    assert_eq!(font.text_size("ab", 1), (4, 3));
+    assert_eq!(font.text_size("ab\nbaa", 1), (6, 6));
+    assert_eq!(font.text_size("ab\nbaa", 3), (18, 18));
+    // Tabs stop every 4 spaces, the space here is 1 pixel wide.
+    assert_eq!(font.text_size("a\tb", 1), (6, 3));
+    assert_eq!(font.text_size("aaa\tb", 1), (10, 3));
+    assert_eq!(font.text_size("a\r\n", 1), (2, 6));
+    assert_eq!(text_size("Hi\nthere", 2), (80, 32));
+}
+
+#[test]
+fn huge_scales_saturate_the_size() {
+    assert_eq!(text_size("Hi", 1 << 27), (1 << 31, 1 << 30));
+    assert_eq!(text_size("Hi", u32::MAX), (u32::MAX, u32::MAX));
+    assert_eq!(text_size("\t\n\n\n", u32::MAX), (u32::MAX, u32::MAX));
+    // Past what i64 holds too: a line height of u32::MAX times a scale of u32::MAX, three lines of it.
+    assert_eq!(GsnFont::new(u32::MAX).text_size("a\na\na", u32::MAX), (u32::MAX, u32::MAX));
+}
+
+#[test]
+fn missing_characters_fall_back() {
+    let mut font = font();
+    // No '?' and no U+FFFD, so the hollow box: half the line height but at least 3 wide, plus a gap.
+    assert_eq!(font.text_size("z", 1), (4, 3));
+    font.set_fallback('a');
+    assert_eq!(font.text_size("z", 1), (2, 3));
+    assert!(!font.contains('z'));
+}
+
+#[test]
+fn draw_text_places_every_glyph() {
+    let mut s = GsnSprite::new(8, 6);
+    s.draw_text(&font(), 1, 0, "ab\nba", WHITE, 1);
+    assert_eq!(grid(&s), [
+        ".#......",
+        "..##....",
+        ".##.....",
+        "...#....",
+        ".#..#...",
+        "...##..."
+    ]);
+
+    let mut s = GsnSprite::new(6, 6);
+    s.draw_text(&font(), 0, 0, "a", WHITE, 2);
+    assert_eq!(grid(&s), ["##....", "##....", "..##..", "..##..", "####..", "####.."]);
+}
+
+#[test]
+fn draw_text_clips_at_the_edges() {
+    let mut s = GsnSprite::new(3, 3);
+    s.draw_text(&font(), -1, -1, "a\na", WHITE, 1);
+    assert_eq!(grid(&s), ["#..", "#..", "..."]);
+    s.draw_text(&font(), i32::MAX, i32::MIN, "aaaa", WHITE, 100);
+    assert_eq!(grid(&s), ["#..", "#..", "..."]);
+}
+
+#[test]
+fn builtin_font_draws_the_bios_glyphs() {
+    let mut s = GsnSprite::new(8, 8);
+    s.draw_string(0, 0, "I", WHITE, 1);
+    assert_eq!(grid(&s), [".####...", "..##....", "..##....", "..##....", "..##....", "..##....", ".####...", "........"]);
+}
@@ -0,0 +1,65 @@
+use engine::time::GsnClock;
+
+#[test]
+fn fixed_steps_bank_leftover_time() {
+    let mut clock = GsnClock::new();
+    clock.set_fixed_step(Some(0.01));
+    clock.advance(0.025);
+    let mut steps = 0;
+    while clock.step() {
+        steps += 1;
+    }
+    assert_eq!(steps, 2);
+    assert!((clock.alpha() - 0.5).abs() < 1e-9);
+
+    clock.advance(0.005);
+    assert!(clock.step());
+    assert!(!clock.step());
+}
+
+#[test]
+fn long_frames_are_clamped() {
+    let mut clock = GsnClock::new();
+    clock.set_fixed_step(Some(0.01));
+    clock.advance(10.0);
+    let mut steps = 0;
+    while clock.step() {
+        steps += 1;
+    }
+    // 0.25s worth, give or take rounding in the accumulator.
+    assert!((24..=25).contains(&steps), "{}", steps);
+}
+
+#[test]
+fn stats_average_recent_frames() {
+    let mut clock = GsnClock::new();
+    for dt in [0.01, 0.02, 0.03] {
+        clock.advance(dt);
+    }
+    let stats = clock.stats();
+    assert_eq!(stats.frames, 3);
+    assert!((stats.frame_time - 0.02).abs() < 1e-9);
+    assert!((stats.fps - 50.0).abs() < 1e-6);
+    assert_eq!(stats.min_frame_time, 0.01);
+    assert_eq!(stats.max_frame_time, 0.03);
+}
+
+#[test]
+fn frame_cap_holds_ticks_apart() {
+    let mut clock = GsnClock::new();
+    clock.set_frame_cap(Some(100.0));
+    clock.tick();
+    let dt = clock.tick();
+    assert!(dt >= 0.01, "{}", dt);
+}
+
+#[test]
+fn caps_too_low_to_wait_out_are_ignored() {
+    let mut clock = GsnClock::new();
+    for fps in [1e-300, f64::MIN_POSITIVE] {
+        clock.set_frame_cap(Some(fps));
+        assert_eq!(clock.frame_cap(), Some(fps));
+        clock.tick();
+        clock.tick();
+    }
+}
@@ -0,0 +1,89 @@
+use engine::blend::GsnBlendMode;
+use engine::renderer::{pixel_rgb, GsnSprite, BLACK, WHITE};
+
+// Additive with a step of 1 counts how many times each pixel was written.
+fn counts(shapes: &[&[(i32, i32)]]) -> Vec<u8> {
+    let mut s = GsnSprite::new(32, 32);
+    s.set_blend_mode(GsnBlendMode::Additive);
+    for points in shapes {
+        s.fill_polygon(points, pixel_rgb(1, 1, 1));
+    }
+    (0..32).flat_map(|y| (0..32).map(move |x| (x, y))).map(|(x, y)| s.get_pixel(x, y).unwrap().r()).collect()
+}
+
+#[test]
+fn shared_edges_are_written_exactly_once() {
+    let quads: [[(i32, i32); 4]; 4] = [
+        [(2, 3), (29, 1), (27, 30), (4, 25)],
+        [(0, 0), (31, 0), (31, 31), (0, 31)],
+        [(16, -5), (40, 16), (16, 37), (-8, 16)],
+        [(3, 7), (11, 2), (30, 29), (5, 21)]
+    ];
+    for [a, b, c, d] in quads {
+        let split = counts(&[&[a, b, c], &[a, c, d]]);
+        let whole = counts(&[&[a, b, c, d]]);
+        assert!(split.iter().all(|&n| n <= 1), "{:?} written twice", [a, b, c, d]);
+        // Same pixels as the quad drawn in one go, so the diagonal leaves no seam.
+        assert_eq!(split, whole, "{:?}", [a, b, c, d]);
+    }
+}
+
+#[test]
+fn triangle_fan_covers_without_overlap() {
+    let center = (15, 14);
+    let rim = [(2, 2), (16, 0), (30, 5), (31, 20), (20, 31), (4, 28), (0, 15)];
+    let fan: Vec<[(i32, i32); 3]> = (0..rim.len()).map(|i| [center, rim[i], rim[(i + 1) % rim.len()]]).collect();
+    let fan_refs: Vec<&[(i32, i32)]> = fan.iter().map(|t| &t[..]).collect();
+    let split = counts(&fan_refs);
+    assert!(split.iter().all(|&n| n <= 1));
+    assert_eq!(split, counts(&[&rim]));
+}
+
+#[test]
+fn triangle_outline_and_fill() {
+    let mut s = GsnSprite::new(6, 5);
+    s.draw_triangle(0, 0, 5, 0, 0, 4, WHITE);
+    let grid: Vec<String> = (0..5).map(|y| (0..6).map(|x| if s.get_pixel(x, y) == Some(BLACK) { '.' } else { '#' }).collect()).collect();
+    assert_eq!(grid, ["######", "#...#.", "#.##..", "##....", "#....."]);
+
+    // Far off vertices only cost the visible part of each edge.
+    s.clear(BLACK);
+    s.draw_triangle(-2_000_000_000, 2, 2_000_000_000, 2, 0, 2_000_000_000, WHITE);
+    s.fill_triangle(-2_000_000_000, -2_000_000_000, 2_000_000_000, -2_000_000_000, 0, 2_000_000_000, WHITE);
+    assert!((0..5).all(|y| (0..6).all(|x| s.get_pixel(x, y) == Some(WHITE))));
+}
+
+fn grid(s: &GsnSprite) -> Vec<String> {
+    (0..s.height()).map(|y| (0..s.width()).map(|x| if s.get_pixel(x, y) == Some(BLACK) { '.' } else { '#' }).collect()).collect()
+}
+
+#[test]
+fn concave_polygon_leaves_the_notch_empty() {
+    let mut s = GsnSprite::new(7, 6);
+    s.fill_polygon(&[(0, 0), (2, 0), (2, 4), (5, 4), (5, 0), (7, 0), (7, 6), (0, 6)], WHITE);
+    assert_eq!(grid(&s), ["##...##", "##...##", "##...##", "##...##", "#######", "#######"]);
+}
+
+#[test]
+fn self_intersecting_polygon_uses_non_zero_winding() {
+    // A five pointed star drawn in one stroke goes around its middle twice. Non-zero winding fills
+    // the middle, where even-odd would have left a hole.
+    let star = [(5, 0), (8, 10), (0, 4), (10, 4), (2, 10)];
+    let mut s = GsnSprite::new(11, 11);
+    s.fill_polygon(&star, WHITE);
+    assert_eq!(grid(&s), [
+        "...........",
+        "...........",
+        "....##.....",
+        "....##.....",
+        ".########..",
+        "..######...",
+        "...####....",
+        "...####....",
+        "..##..##...",
+        "..#....#...",
+        "..........."
+    ]);
+    // Covered twice, but still written once.
+    assert!(counts(&[&star]).iter().all(|&n| n <= 1));
+}
@@ -0,0 +1,138 @@
+use engine::font::GsnTrueType;
+use engine::renderer::{GsnSprite, BLACK, WHITE};
+
+fn grid(s: &GsnSprite) -> Vec<String> {
+    (0..s.height()).map(|y| (0..s.width()).map(|x| if s.get_pixel(x, y) == Some(BLACK) { '.' } else { '#' }).collect()).collect()
+}
+
+// 500 unit square with its corner on the origin.
+fn square() -> Vec<u8> {
+    let mut g = vec![0, 1, 0, 0, 0, 0, 1, 0xf4, 1, 0xf4, 0, 3, 0, 0];
+    g.extend([1; 4]);
+    for delta in [0i16, 500, 0, -500, 0, 0, 500, 0] {
+        g.extend(delta.to_be_bytes());
+    }
+    g
+}
+
+// Components given as (glyph, dx, dy) in font units.
+fn composite(components: &[(u16, i16, i16)]) -> Vec<u8> {
+    let mut g = vec![0xff, 0xff, 0, 0, 0, 0, 0, 0, 0, 0];
+    for (i, &(glyph, dx, dy)) in components.iter().enumerate() {
+        let more = if i + 1 < components.len() { 0x20u16 } else { 0 };
+        g.extend((0x0003 | more).to_be_bytes());
+        g.extend(glyph.to_be_bytes());
+        g.extend(dx.to_be_bytes());
+        g.extend(dy.to_be_bytes());
+    }
+    g
+}
+
+// 1000 units per em, ascent 800, descent -200. The character map is a format 12 one made of (first, last, glyph) groups.
+fn font(glyphs: &[Vec<u8>], groups: &[(char, char, u32)]) -> Vec<u8> {
+    let mut head = vec![0; 54];
+    head[18..20].copy_from_slice(&1000u16.to_be_bytes());
+    head[50..52].copy_from_slice(&1i16.to_be_bytes());
+    let mut maxp = vec![0, 0, 0x50, 0];
+    maxp.extend((glyphs.len() as u16).to_be_bytes());
+    let mut hhea = vec![0; 36];
+    hhea[4..6].copy_from_slice(&800i16.to_be_bytes());
+    hhea[6..8].copy_from_slice(&(-200i16).to_be_bytes());
+    hhea[34..36].copy_from_slice(&(glyphs.len() as u16).to_be_bytes());
+
+    let (mut glyf, mut loca, mut hmtx) = (Vec::new(), Vec::new(), Vec::new());
+    for g in glyphs {
+        loca.extend((glyf.len() as u32).to_be_bytes());
+        glyf.extend(g);
+        hmtx.extend(600u16.to_be_bytes());
+        hmtx.extend(0i16.to_be_bytes());
+    }
+    loca.extend((glyf.len() as u32).to_be_bytes());
+
+    let mut cmap = vec![0, 0, 0, 1, 0, 3, 0, 10, 0, 0, 0, 12];
+    cmap.extend([0, 12, 0, 0]);
+    cmap.extend((16 + groups.len() as u32 * 12).to_be_bytes());
+    cmap.extend([0; 4]);
+    cmap.extend((groups.len() as u32).to_be_bytes());
+    for &(first, last, glyph) in groups {
+        for v in [first as u32, last as u32, glyph] {
+            cmap.extend(v.to_be_bytes());
+        }
+    }
+
+    let tables = [(b"cmap", cmap), (b"glyf", glyf), (b"head", head), (b"hhea", hhea), (b"hmtx", hmtx), (b"loca", loca), (b"maxp", maxp)];
+    let mut out = vec![0, 1, 0, 0];
+    out.extend((tables.len() as u16).to_be_bytes());
+    out.extend([0; 6]);
+    let mut offset = 12 + tables.len() * 16;
+    for (tag, data) in &tables {
+        out.extend(*tag);
+        out.extend([0; 4]);
+        out.extend((offset as u32).to_be_bytes());
+        out.extend((data.len() as u32).to_be_bytes());
+        offset += data.len().next_multiple_of(4);
+    }
+    for (_, data) in &tables {
+        out.extend(data);
+        out.resize(out.len().next_multiple_of(4), 0);
+    }
+    out
+}
+
+// A square, two squares side by side, a composite tree far too big to flatten, a glyph made of itself
+// and a group whose glyph ids run past u32::MAX.
+fn test_font() -> GsnTrueType {
+    let mut glyphs = vec![vec![], square(), composite(&[(1, 0, 0), (1, 600, 0)])];
+    for next in 4..=10 {
+        glyphs.push(composite(&[(next, 0, 0); 16]));
+    }
+    glyphs.push(square());
+    glyphs.push(composite(&[(11, 0, 0)]));
+    let groups = [('A', 'A', 1), ('B', 'B', 2), ('C', 'C', 3), ('D', 'D', 11), ('E', 'Z', u32::MAX - 4)];
+    GsnTrueType::from_bytes(font(&glyphs, &groups)).unwrap()
+}
+
+#[test]
+fn outlines_cover_whole_pixels() {
+    let mut font = test_font();
+    assert_eq!((font.glyph_count(), font.line_height(10)), (12, 10));
+    assert_eq!(font.text_size("AB", 10), (12, 10));
+
+    let a = font.glyph('A', 10);
+    assert_eq!((a.width, a.height, a.x_offset, a.y_offset), (5, 5, 0, 3));
+    assert!(a.coverage.iter().all(|&c| c == 255));
+
+    let mut s = GsnSprite::new(12, 10);
+    s.draw_truetype(&mut font, 0, 0, "B", WHITE, 10);
+    let row = "#####.#####.";
+    assert_eq!(grid(&s), ["............", "............", "............", row, row, row, row, row, "............", "............"]);
+}
+
+#[test]
+fn hostile_glyphs_draw_nothing() {
+    let mut font = test_font();
+    // 16 components nested 7 deep, flattening it would take 16^7 outlines.
+    assert!(font.contains('C'));
+    assert_eq!(font.glyph('C', 10).width, 0);
+    assert_eq!(font.glyph('D', 10).width, 0);
+    assert_eq!(font.glyph('D', 10).advance, 6.0);
+
+    // The group for 'E' to 'Z' starts past the 16 bit glyph ids and runs past u32::MAX from 'J' on.
+    assert!(!font.contains('E') && !font.contains('Z'));
+
+    // A square a million pixels across would need a terabyte of coverage.
+    let huge = font.glyph('A', 2_000_000);
+    assert_eq!((huge.width, huge.height), (0, 0));
+    let mut s = GsnSprite::new(4, 4);
+    s.draw_truetype(&mut font, 0, 0, "ACDZ", WHITE, 2_000_000);
+    assert_eq!(grid(&s), ["....", "....", "....", "...."]);
+}
+
+#[test]
+fn truncated_fonts_are_decode_errors() {
+    let bytes = font(&[vec![], square()], &[('A', 'A', 1)]);
+    for len in [0, 3, 12, 40, 100] {
+        assert!(GsnTrueType::from_bytes(bytes[..len].to_vec()).is_err(), "cut to {}", len);
+    }
+    assert!(GsnTrueType::from_bytes(b"OTTO and the rest".to_vec()).is_err());
+}
@@ -0,0 +1,60 @@
+use engine::blit::GsnRect;
+use engine::renderer::{GsnUploadMode, GsnUploadStats, WHITE};
+use engine::GsnEngine;
+
+#[test]
+fn only_changed_pixels_are_uploaded() {
+    let mut engine = GsnEngine::headless(320, 240);
+    engine.render();
+    assert_eq!(engine.upload_stats(), GsnUploadStats { full_uploads: 1, partial_uploads: 0, skipped_uploads: 0, rects: 1, pixels: 320 * 240 });
+
+    engine.render();
+    assert_eq!(engine.upload_stats().skipped_uploads, 1);
+
+    engine.buffer().fill_rect(100, 50, 16, 16, WHITE);
+    assert_eq!(engine.buffer().dirty_rects(), &[GsnRect::new(100, 50, 16, 16)]);
+    engine.render();
+    let stats = engine.upload_stats();
+    assert_eq!((stats.partial_uploads, stats.pixels), (1, 320 * 240 + 16 * 16));
+}
+
+#[test]
+fn separate_regions_stay_separate() {
+    let mut engine = GsnEngine::headless(320, 240);
+    engine.render();
+    engine.buffer().fill_rect(0, 0, 8, 8, WHITE);
+    engine.buffer().fill_rect(300, 200, 8, 8, WHITE);
+    assert_eq!(engine.buffer().dirty_rects(), &[GsnRect::new(0, 0, 8, 8), GsnRect::new(300, 200, 8, 8)]);
+    engine.render();
+    assert_eq!(engine.upload_stats().pixels, 320 * 240 + 2 * 64);
+}
+
+#[test]
+fn scattered_writes_merge_and_big_changes_go_full() {
+    let mut engine = GsnEngine::headless(100, 100);
+    engine.render();
+    for i in 0..20 {
+        engine.buffer().set_pixel(i * 5, i * 5, WHITE);
+    }
+    // Past eight rectangles they collapse into their bounding box and start again.
+    let dirty = engine.buffer().dirty_rects();
+    assert!(dirty.len() <= 8);
+    assert_eq!(dirty[0], GsnRect::new(0, 0, 81, 81));
+    engine.render();
+    assert_eq!(engine.upload_stats().full_uploads, 2);
+
+    engine.buffer().clear(WHITE);
+    engine.render();
+    assert_eq!(engine.upload_stats().full_uploads, 3);
+}
+
+#[test]
+fn headless_streaming_counts_the_same() {
+    let mut engine = GsnEngine::headless(64, 64);
+    engine.set_upload_mode(GsnUploadMode::Streaming);
+    assert_eq!(engine.upload_mode(), GsnUploadMode::Streaming);
+    engine.render();
+    engine.buffer().fill_rect(0, 0, 4, 4, WHITE);
+    engine.render();
+    assert_eq!(engine.upload_stats().pixels, 64 * 64 + 16);
+}
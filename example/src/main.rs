@@ -2,73 +2,117 @@ use std::collections::HashMap;
 use engine;
 use rand;
 use rand::Rng;
-use engine::{GsnEvent, GsnEngine, GsnKey, GsnAction};
-use engine::renderer::{BLACK, GsnSprite, Pixel, pixel_rgb};
+use engine::{GsnEvent, GsnEngine, GsnKey, GsnAction, GsnWindowMode};
+use engine::app::GsnApp;
+use engine::input::GsnBinding;
+use engine::renderer::{BLACK, WHITE, GsnSprite, Pixel, pixel_rgb};
 
 #[derive(Debug,Copy,Clone)]
 pub struct Entity {
     pub x: f64,
     pub y: f64,
+    // Pixels per second.
     pub speed: f64,
     pub color: Pixel
 }
 impl Entity {
-    fn left(&mut self) {
-        self.x -= self.speed;
+    fn left(&mut self, dt: f64) {
+        self.x -= self.speed * dt;
     }
-    fn right(&mut self) {
-        self.x += self.speed;
+    fn right(&mut self, dt: f64) {
+        self.x += self.speed * dt;
     }
-    fn up(&mut  self) {
-        self.y += self.speed;
+    fn up(&mut  self, dt: f64) {
+        self.y -= self.speed * dt;
     }
-    fn down(&mut self) {
-        self.y -= self.speed;
+    fn down(&mut self, dt: f64) {
+        self.y += self.speed * dt;
     }
     fn draw(&mut self,buffer: &mut GsnSprite) {
         buffer.fill_rect(self.x as u32,self.y as u32,16,16,self.color);
     }
 }
 
-fn main() {
-    // Setting up the window.
-    let mut game = GsnEngine::new(
-        "This is a window",
-        1024,
-        786,
-        engine::GsnWindowMode::WINDOWED);
+struct Game {
+    player: Entity,
+    wall: Option<GsnSprite>
+}
 
+impl GsnApp for Game {
+    fn on_create(&mut self, game: &mut GsnEngine) {
+        // Controls, WASD and the arrow keys both work.
+        for (action, keys) in [
+            ("left", [GsnKey::A, GsnKey::Left]),
+            ("right", [GsnKey::D, GsnKey::Right]),
+            ("up", [GsnKey::W, GsnKey::Up]),
+            ("down", [GsnKey::S, GsnKey::Down])
+        ] {
+            for key in keys {
+                if let Err(e) = game.input_map.bind(action, GsnBinding::Key(key)) {
+                    eprintln!("Could not bind {}: {}", action, e);
+                }
+            }
+        }
+    }
 
-    let mut player = Entity {
-        x: 0.0,
-        y: 0.0,
-        speed: 8.0,
-        color: pixel_rgb(128,128,0)
-    };
+    fn on_update(&mut self, game: &mut GsnEngine, dt: f64) {
+        if game.action_held("left") {self.player.left(dt)}
+        if game.action_held("right") {self.player.right(dt)}
+        if game.action_held("up") {self.player.up(dt)}
+        if game.action_held("down") {self.player.down(dt)}
 
-    // The looping.
-    'running: loop {
-        game.update();
-        while !game.actions.is_empty() {
-            match game.actions.pop().unwrap() {
-                GsnEvent::Draw => {
-                    if game.key_held(GsnKey::A) {player.left()}
-                    if game.key_held(GsnKey::D){player.right()}
-                    if game.key_held(GsnKey::W) {player.up()}
-                    if game.key_held(GsnKey::S) {player.down()}
+        game.buffer().clear(BLACK);
+        if let Some(wall) = &self.wall {
+            game.buffer().draw_sprite(wall, 0, 0);
+        }
 
-                    game.buffer().clear(BLACK);
+        self.player.draw(game.buffer());
+        game.buffer().draw_string(8, 8, "WASD to move, F11 fullscreen, Esc to quit", WHITE, 2);
+    }
 
-                    player.draw(game.buffer());
-                }
-                GsnEvent::KeyPress(k, GsnAction::Press) => {
-                    match k {
-                        GsnKey::Escape => {break 'running}
-                        _ => {}
-                    }
-                }
-                _ => {}
+    fn on_event(&mut self, game: &mut GsnEngine, event: &GsnEvent) {
+        match event {
+            GsnEvent::KeyPress(GsnKey::Escape, GsnAction::Press, _) => game.exit(),
+            GsnEvent::KeyPress(GsnKey::F11, GsnAction::Press, _) => {
+                let mode = if game.window_mode() == GsnWindowMode::WINDOWED { GsnWindowMode::BORDERLESS(0) } else { GsnWindowMode::WINDOWED };
+                game.set_window_mode(mode);
             }
+            _ => {}
         }
     }
 }
+
+fn main() {
+    // Setting up the window.
+    let game = GsnEngine::builder("This is a window", 1024, 786)
+        .mode(GsnWindowMode::WINDOWED)
+        .resizable(true)
+        .vsync(true)
+        .build();
+    let mut game = match game {
+        Ok(game) => game,
+        Err(e) => {
+            eprintln!("Could not start: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut app = Game {
+        player: Entity {
+            x: 0.0,
+            y: 0.0,
+            speed: 480.0,
+            color: pixel_rgb(128,128,0)
+        },
+        wall: match GsnSprite::load_jpeg(concat!(env!("CARGO_MANIFEST_DIR"), "/../wall.jpg")) {
+            Ok(wall) => Some(wall),
+            Err(e) => {
+                eprintln!("Could not load wall.jpg: {}", e);
+                None
+            }
+        }
+    };
+
+    // The looping.
+    game.run(&mut app);
+}